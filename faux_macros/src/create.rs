@@ -1,6 +1,6 @@
 use crate::self_type::SelfType;
 use darling::FromMeta;
-use quote::quote;
+use quote::{quote, quote_spanned};
 
 #[derive(Default, FromMeta)]
 #[darling(default)]
@@ -10,26 +10,55 @@ pub struct Args {
 }
 
 pub struct Mockable {
-    // the real definition of the struct
-    real: syn::ItemStruct,
+    // the real definition of the struct or enum
+    real: syn::Item,
     // the morphed definition, wraps the real struct around a MaybeFaux
     morphed: syn::ItemStruct,
+    // the variants of the real enum, none for a struct
+    variants: Vec<syn::Variant>,
 }
 
 impl Mockable {
-    pub fn new(original: syn::ItemStruct, args: Args) -> Self {
-        // clone original before changing anything
-        let mut morphed = original.clone();
-
-        // change the name of the original struct and then stop mutating it
-        let mut real = original;
-        real.ident = real_struct_new_ident(&real.ident);
-        let real = real;
+    pub fn new(original: syn::Item, args: Args) -> darling::Result<Self> {
+        // clone original before changing anything, and change the name
+        // of the original definition
+        let (mut morphed, real, variants) = match original {
+            syn::Item::Struct(mut real) => {
+                let morphed = real.clone();
+                real.ident = real_struct_new_ident(&real.ident);
+                (morphed, syn::Item::Struct(real), vec![])
+            }
+            syn::Item::Enum(mut real) => {
+                let morphed = syn::ItemStruct {
+                    attrs: real.attrs.clone(),
+                    vis: real.vis.clone(),
+                    struct_token: syn::Token![struct](real.enum_token.span),
+                    ident: real.ident.clone(),
+                    generics: real.generics.clone(),
+                    fields: syn::Fields::Unit,
+                    semi_token: None,
+                };
+                let variants = real.variants.iter().cloned().collect();
+                real.ident = real_struct_new_ident(&real.ident);
+                (morphed, syn::Item::Enum(real), variants)
+            }
+            item => {
+                return Err(darling::Error::custom(
+                    "#[faux::create] only supports structs and enums",
+                )
+                .with_span(&item))
+            }
+        };
+        let real_ident = match &real {
+            syn::Item::Struct(real) => &real.ident,
+            syn::Item::Enum(real) => &real.ident,
+            _ => unreachable!(),
+        };
 
         // change the fields in morphed to wrap the original struct
         morphed.fields = {
             let wrapped_self = {
-                let modified_name = &real.ident;
+                let modified_name = real_ident;
                 let (_, ty_generics, _) = morphed.generics.split_for_impl();
 
                 match args.self_type {
@@ -43,17 +72,72 @@ impl Mockable {
             syn::Fields::Unnamed(syn::parse_quote! { (#vis faux::MaybeFaux<#wrapped_self>) })
         };
 
-        Mockable { real, morphed }
+        Ok(Mockable {
+            real,
+            morphed,
+            variants,
+        })
     }
 }
 
 impl From<Mockable> for proc_macro::TokenStream {
     fn from(mockable: Mockable) -> Self {
-        let Mockable { real, morphed } = mockable;
-        let (impl_generics, ty_generics, where_clause) = real.generics.split_for_impl();
+        let Mockable {
+            real,
+            morphed,
+            variants,
+        } = mockable;
+        let (impl_generics, ty_generics, where_clause) = morphed.generics.split_for_impl();
         let name = &morphed.ident;
         let name_str = name.to_string();
 
+        // the variants are only reachable within #[faux::methods], so
+        // stand-ins for unit and tuple variants fail to evaluate a
+        // constant that panics with the explanation once used. The
+        // error points at the variant. Tuple variants in patterns and
+        // struct variants cannot be stood in for, and are left to the
+        // compiler's errors.
+        let note = format!(
+            "faux: `{}` is a mockable enum so its variants may only be used within #[faux::methods]",
+            name
+        );
+        let variants: Vec<_> = variants
+            .iter()
+            .filter_map(|variant| {
+                let ident = &variant.ident;
+                let panic = quote_spanned! { ident.span()=> panic!(#note) };
+                match &variant.fields {
+                    syn::Fields::Unit => Some(quote! {
+                        #[doc(hidden)]
+                        #[allow(non_upper_case_globals, dead_code)]
+                        pub const #ident: Self = #panic;
+                    }),
+                    syn::Fields::Unnamed(fields) => {
+                        let args: Vec<_> = (0..fields.unnamed.len())
+                            .map(|i| quote::format_ident!("_A{}", i))
+                            .collect();
+                        let used = quote_spanned! { ident.span()=>
+                            let () = Variant::<(#(#args,)*)>::USED;
+                        };
+                        Some(quote! {
+                            #[doc(hidden)]
+                            #[allow(non_snake_case, dead_code)]
+                            pub fn #ident<#(#args),*>(#(_: #args),*) -> Self {
+                                // only evaluated once the function is used
+                                struct Variant<T>(T);
+                                impl<T> Variant<T> {
+                                    const USED: () = #panic;
+                                }
+                                #[allow(clippy::let_unit_value)]
+                                #used
+                                unreachable!()
+                            }
+                        })
+                    }
+                    syn::Fields::Named(_) => None,
+                }
+            })
+            .collect();
         proc_macro::TokenStream::from(quote! {
             #morphed
 
@@ -61,6 +145,30 @@ impl From<Mockable> for proc_macro::TokenStream {
                 pub fn faux() -> Self {
                     Self(faux::MaybeFaux::faux(#name_str))
                 }
+
+                #(#variants)*
+            }
+
+            impl #impl_generics faux::Mockable for #name #ty_generics #where_clause {
+                const NAME: &'static str = #name_str;
+
+                fn faux() -> Self {
+                    Self(faux::MaybeFaux::faux(#name_str))
+                }
+
+                fn is_faux(&self) -> bool {
+                    self.0.faux_ref().is_some()
+                }
+
+                fn faux_store(&self) -> std::option::Option<faux::MockStore<'_>> {
+                    self.0.faux_ref().map(faux::MockStore::new)
+                }
+            }
+
+            impl #impl_generics faux::sealed::Sealed for #name #ty_generics #where_clause {
+                fn faux_mut(&mut self, _: faux::sealed::Token) -> std::option::Option<&mut faux::Faux> {
+                    self.0.faux_mut()
+                }
             }
 
             #[allow(non_camel_case_types)]
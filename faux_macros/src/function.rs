@@ -0,0 +1,159 @@
+use crate::methods::morphed::{self, WhenArg};
+use quote::quote;
+
+pub struct Mockable {
+    // the function with its arguments renamed to identifiers
+    real: syn::ItemFn,
+    // patterns of the original arguments that were not identifiers
+    patterns: Vec<(syn::Ident, syn::Pat)>,
+}
+
+impl Mockable {
+    pub fn new(mut real: syn::ItemFn) -> darling::Result<Self> {
+        if let Some(receiver) = real.sig.receiver() {
+            return Err(darling::Error::custom(
+                "#[faux::function] does not support methods. Use #[faux::methods] instead",
+            )
+            .with_span(receiver));
+        }
+
+        // the stubs are given the arguments so each needs a name
+        let mut patterns = vec![];
+        real.sig
+            .inputs
+            .iter_mut()
+            .filter_map(|arg| match arg {
+                syn::FnArg::Typed(arg) => Some(arg),
+                syn::FnArg::Receiver(_) => None,
+            })
+            .enumerate()
+            .for_each(|(i, arg)| match arg.pat.as_ref() {
+                syn::Pat::Ident(syn::PatIdent {
+                    by_ref: None,
+                    subpat: None,
+                    ..
+                }) => {}
+                _ => {
+                    let ident = quote::format_ident!("_faux_arg_{}", i);
+                    let pat = std::mem::replace(arg.pat.as_mut(), syn::parse_quote! { #ident });
+                    patterns.push((ident, pat));
+                }
+            });
+
+        Ok(Mockable { real, patterns })
+    }
+}
+
+impl From<Mockable> for proc_macro::TokenStream {
+    fn from(mockable: Mockable) -> Self {
+        let Mockable { real, patterns } = mockable;
+        let syn::ItemFn {
+            attrs,
+            vis,
+            sig,
+            block,
+        } = real;
+
+        let name = &sig.ident;
+        let when_ident = quote::format_ident!("_when_{}", name);
+        let faux_ident = quote::format_ident!("_faux_{}", name);
+
+        let generics = &sig.generics;
+        let generics_where_clause = &generics.where_clause;
+        let generic_idents = morphed::generic_type_idents(Some(generics.clone()));
+        let turbofish = morphed::turbofish(&generic_idents);
+        let method_id = morphed::method_id(name, &generic_idents);
+        let mut generics_str = generic_idents
+            .iter()
+            .map(|i| i.to_string())
+            .collect::<Vec<_>>()
+            .join(",");
+        generics_str.retain(|c| !c.is_whitespace());
+
+        let (args, arg_types): (Vec<_>, Vec<_>) = sig
+            .inputs
+            .iter()
+            .filter_map(|arg| match arg {
+                syn::FnArg::Typed(syn::PatType { pat, ty, .. }) => match pat.as_ref() {
+                    syn::Pat::Ident(pat) => Some((&pat.ident, WhenArg(ty))),
+                    _ => None,
+                },
+                syn::FnArg::Receiver(_) => None,
+            })
+            .unzip();
+
+        let empty = syn::parse_quote! { () };
+        let output = match &sig.output {
+            syn::ReturnType::Default => &empty,
+            syn::ReturnType::Type(_, ty) => ty.as_ref(),
+        };
+
+        let mut stub_args = args.iter().zip(arg_types.iter()).map(|(ident, ty)| {
+            if morphed::has_impl_trait(ty.0) {
+                quote! { std::boxed::Box::new(#ident) }
+            } else {
+                quote! { #ident }
+            }
+        });
+        let stub_args = if stub_args.len() == 1 {
+            let arg = stub_args.next().unwrap();
+            quote! { #arg }
+        } else {
+            quote! { (#(#stub_args,)*) }
+        };
+
+        let call_stub = if sig.asyncness.is_some() {
+            quote! { call_stub_async(#faux_ident #turbofish, #method_id, _faux_input, _faux_describe, #generics_str).await }
+        } else {
+            quote! { call_stub(#faux_ident #turbofish, #method_id, _faux_input, _faux_describe, #generics_str) }
+        };
+        let generics_in_scope: Vec<_> = generic_idents.iter().collect();
+        let keep_input = arg_types
+            .iter()
+            .all(|ty| morphed::is_owned(ty.0, &generics_in_scope));
+        let describe_input = morphed::describe_input(&arg_types, keep_input);
+
+        let (pattern_idents, patterns): (Vec<_>, Vec<_>) = patterns.into_iter().unzip();
+        let panic_message = format!("do not call this ({})", faux_ident);
+
+        proc_macro::TokenStream::from(quote! {
+            #(#attrs)*
+            #vis #sig {
+                // call the stubs of this thread if the function was
+                // stubbed, else run the real function
+                match faux::Associated::stubbed_function(module_path!(), #method_id) {
+                    std::option::Option::Some(_maybe_faux_faux) => unsafe {
+                        let _faux_input = faux::arg_debug::input(#faux_ident #turbofish, #stub_args);
+                        let _faux_describe = #describe_input;
+                        match _maybe_faux_faux.#call_stub {
+                            std::result::Result::Ok(o) => o,
+                            std::result::Result::Err(e) => panic!("{}", e),
+                        }
+                    },
+                    std::option::Option::None => {
+                        #(let #patterns = #pattern_idents;)*
+                        #block
+                    }
+                }
+            }
+
+            #[doc(hidden)]
+            #[track_caller]
+            #[allow(dead_code)]
+            #vis fn #when_ident #generics (_faux_stubs: &mut faux::Associated) -> faux::When<'_, (), (#(#arg_types),*), #output, faux::matcher::AnyInvocation> #generics_where_clause {
+                faux::When::new(
+                    #faux_ident #turbofish,
+                    #method_id,
+                    false,
+                    _faux_stubs.functions(module_path!())
+                )
+            }
+
+            #[doc(hidden)]
+            #[allow(clippy::boxed_local)]
+            #vis fn #faux_ident #generics (_: (), _: (#(#arg_types),*)) -> #output #generics_where_clause {
+                panic!(#panic_message)
+            }
+        })
+    }
+}
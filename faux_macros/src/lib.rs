@@ -1,8 +1,11 @@
 extern crate proc_macro;
 
 mod create;
+mod function;
 mod methods;
+mod mock_extern;
 mod self_type;
+mod trait_mock;
 
 use darling::{export::NestedMeta, FromMeta};
 use proc_macro::TokenStream;
@@ -10,49 +13,236 @@ use quote::quote;
 
 #[proc_macro_attribute]
 pub fn create(args: TokenStream, original: TokenStream) -> TokenStream {
-    let original = syn::parse_macro_input!(original as syn::ItemStruct);
-
     let args = match NestedMeta::parse_meta_list(args.into())
         .map_err(darling::Error::from)
-        .and_then(|v| create::Args::from_list(&v))
+        .and_then(split_cfg)
     {
-        Ok(v) => v,
+        Ok((Some(cfg), args)) => return cfg_gated(quote! { faux::create }, cfg, args, original),
+        Ok((None, args)) => args,
         Err(e) => return e.write_errors().into(),
     };
 
-    let mockable = create::Mockable::new(original, args);
+    let original = syn::parse_macro_input!(original as syn::Item);
+
+    let args = match create::Args::from_list(&args) {
+        Ok(v) => v,
+        Err(e) => return e.write_errors().into(),
+    };
 
-    TokenStream::from(mockable)
+    match create::Mockable::new(original, args) {
+        Ok(mockable) => TokenStream::from(mockable),
+        Err(e) => e.write_errors().into(),
+    }
 }
 
 #[proc_macro_attribute]
 pub fn methods(args: TokenStream, original: TokenStream) -> TokenStream {
+    let args = match NestedMeta::parse_meta_list(args.into())
+        .map_err(darling::Error::from)
+        .and_then(split_cfg)
+    {
+        Ok((Some(cfg), args)) => return cfg_gated(quote! { faux::methods }, cfg, args, original),
+        Ok((None, args)) => args,
+        Err(e) => return e.write_errors().into(),
+    };
+
     let original = syn::parse_macro_input!(original as syn::ItemImpl);
 
+    let args = match methods::Args::from_list(&args) {
+        Ok(v) => v,
+        Err(e) => return e.write_errors().into(),
+    };
+
+    match methods::Mockable::new(original, args) {
+        Ok(mockable) => TokenStream::from(mockable),
+        Err(e) => e.write_errors().into(),
+    }
+}
+
+#[proc_macro_attribute]
+pub fn trait_mock(args: TokenStream, original: TokenStream) -> TokenStream {
+    let original = syn::parse_macro_input!(original as syn::ItemTrait);
+
     let args = match NestedMeta::parse_meta_list(args.into())
         .map_err(darling::Error::from)
-        .and_then(|v| methods::Args::from_list(&v))
+        .and_then(|v| trait_mock::Args::from_list(&v))
     {
         Ok(v) => v,
         Err(e) => return e.write_errors().into(),
     };
 
-    match methods::Mockable::new(original, args) {
+    match trait_mock::Mockable::new(original, args) {
+        Ok(mockable) => TokenStream::from(mockable),
+        Err(e) => e.write_errors().into(),
+    }
+}
+
+#[proc_macro_attribute]
+pub fn function(args: TokenStream, original: TokenStream) -> TokenStream {
+    let args = match NestedMeta::parse_meta_list(args.into())
+        .map_err(darling::Error::from)
+        .and_then(split_cfg)
+    {
+        Ok((Some(cfg), args)) if args.is_empty() => {
+            return cfg_gated(quote! { faux::function }, cfg, args, original)
+        }
+        Ok((_, args)) => args,
+        Err(e) => return e.write_errors().into(),
+    };
+    if let Some(arg) = args.first() {
+        return darling::Error::custom("#[faux::function] only takes a `cfg` argument")
+            .with_span(arg)
+            .write_errors()
+            .into();
+    }
+
+    let original = syn::parse_macro_input!(original as syn::ItemFn);
+
+    match function::Mockable::new(original) {
         Ok(mockable) => TokenStream::from(mockable),
         Err(e) => e.write_errors().into(),
     }
 }
 
+#[proc_macro]
+pub fn mock_extern(input: TokenStream) -> TokenStream {
+    let mockable = syn::parse_macro_input!(input as mock_extern::Mockable);
+
+    match mockable.validate() {
+        Ok(()) => TokenStream::from(mockable),
+        Err(e) => e.write_errors().into(),
+    }
+}
+
 #[proc_macro]
 pub fn when(input: proc_macro::TokenStream) -> proc_macro::TokenStream {
-    match syn::parse_macro_input!(input as syn::Expr) {
+    let expr = syn::parse_macro_input!(input as syn::Expr);
+    let when = match expr {
+        // not wrapped in a block so the lease on the stubs lives until
+        // the end of the statement
+        syn::Expr::Path(syn::ExprPath { qself, path, .. }) => {
+            associated_with_args(qself, path, vec![])
+        }
+        syn::Expr::Call(syn::ExprCall { func, args, .. })
+            if matches!(func.as_ref(), syn::Expr::Path(_)) =>
+        {
+            match *func {
+                syn::Expr::Path(syn::ExprPath { qself, path, .. }) => {
+                    associated_with_args(qself, path, args.into_iter().collect())
+                }
+                _ => unreachable!(),
+            }
+        }
+        expr => method_with_args(expr, "_when_", "when").map(|when| quote!({ #when })),
+    };
+
+    match when {
+        Ok(when) => TokenStream::from(when),
+        Err(e) => e.write_errors().into(),
+    }
+}
+
+#[proc_macro]
+pub fn await_calls(input: proc_macro::TokenStream) -> proc_macro::TokenStream {
+    let parser = syn::punctuated::Punctuated::<syn::Expr, syn::Token![,]>::parse_terminated;
+    let input = syn::parse_macro_input!(input with parser);
+    let mut input = input.into_iter();
+
+    let calls = match input.next() {
+        // calls matching any argument are counted since the mock's
+        // creation rather than since the macro was invoked
+        Some(syn::Expr::MethodCall(mut call))
+            if call.args.iter().all(|a| matches!(a, syn::Expr::Infer(_))) =>
+        {
+            call.args.clear();
+            method_with_args(syn::Expr::MethodCall(call), "_calls_", "await_calls")
+        }
+        Some(expr) => method_with_args(expr, "_calls_", "await_calls"),
+        None => Err(darling::Error::custom(
+            "faux::await_calls! expects arguments in the format of: `await_calls!(receiver.method(args...), count, timeout)`",
+        )),
+    };
+    let calls = match calls {
+        Ok(calls) => calls,
+        Err(e) => return e.write_errors().into(),
+    };
+
+    match (input.next(), input.next(), input.next()) {
+        (Some(count), Some(timeout), None) => {
+            TokenStream::from(quote!({ #calls.wait(#count, #timeout) }))
+        }
+        (Some(count), None, None) => TokenStream::from(quote!({ #calls.wait_async(#count) })),
+        _ => darling::Error::custom(
+            "faux::await_calls! expects arguments in the format of: `await_calls!(receiver.method(args...), count, timeout)` or `await_calls!(receiver.method(args...), count)`",
+        )
+        .write_errors()
+        .into(),
+    }
+}
+
+/// Takes the `cfg = "..."` argument out of the attribute arguments
+fn split_cfg(args: Vec<NestedMeta>) -> darling::Result<(Option<syn::Meta>, Vec<NestedMeta>)> {
+    let mut cfg = None;
+    let mut rest = vec![];
+    for arg in args {
+        match arg {
+            NestedMeta::Meta(syn::Meta::NameValue(arg)) if arg.path.is_ident("cfg") => {
+                let predicate = match &arg.value {
+                    syn::Expr::Lit(syn::ExprLit {
+                        lit: syn::Lit::Str(predicate),
+                        ..
+                    }) => predicate.parse().map_err(darling::Error::from)?,
+                    value => {
+                        return Err(darling::Error::custom(
+                            "expected a string with a cfg predicate, e.g. `cfg = \"test\"`",
+                        )
+                        .with_span(value))
+                    }
+                };
+                if cfg.replace(predicate).is_some() {
+                    return Err(darling::Error::duplicate_field("cfg").with_span(&arg));
+                }
+            }
+            arg => rest.push(arg),
+        }
+    }
+    Ok((cfg, rest))
+}
+
+/// Expands to the mockable item when `cfg` is set, and to the
+/// original item otherwise
+fn cfg_gated(
+    attribute: proc_macro2::TokenStream,
+    cfg: syn::Meta,
+    args: Vec<NestedMeta>,
+    original: TokenStream,
+) -> TokenStream {
+    let original = proc_macro2::TokenStream::from(original);
+    TokenStream::from(quote! {
+        #[cfg(#cfg)]
+        #[#attribute(#(#args),*)]
+        #original
+
+        #[cfg(not(#cfg))]
+        #original
+    })
+}
+
+/// Converts `receiver.method` or `receiver.method(args...)` to a call
+/// to the generated `{prefix}method` with the argument matchers
+fn method_with_args(
+    expr: syn::Expr,
+    prefix: &str,
+    macro_name: &str,
+) -> darling::Result<proc_macro2::TokenStream> {
+    match expr {
         syn::Expr::Field(syn::ExprField {
             base,
             member: syn::Member::Named(ident),
             ..
         }) => {
-            let when = quote::format_ident!("_when_{}", ident);
-            TokenStream::from(quote!( { #base.#when() }))
+            let method = quote::format_ident!("{}{}", prefix, ident);
+            Ok(quote!( #base.#method() ))
         }
         syn::Expr::MethodCall(syn::ExprMethodCall {
             receiver,
@@ -61,23 +251,57 @@ pub fn when(input: proc_macro::TokenStream) -> proc_macro::TokenStream {
             turbofish,
             ..
         }) => {
-            let when = quote::format_ident!("_when_{}", method);
+            let method = quote::format_ident!("{}{}", prefix, method);
 
             let args = args
                 .into_iter()
                 .map(expr_to_matcher)
-                .collect::<Result<Vec<_>, _>>();
+                .collect::<Result<Vec<_>, _>>()?;
 
-            match args {
-                Err(e) => e.write_errors().into(),
-                Ok(args) if args.is_empty() => { TokenStream::from(quote!({ #receiver.#when #turbofish() }))}
-                Ok(args) => { TokenStream::from(quote!({ #receiver.#when #turbofish().with_args((#(#args,)*)) }))}
+            if args.is_empty() {
+                Ok(quote!( #receiver.#method #turbofish() ))
+            } else {
+                Ok(quote!( #receiver.#method #turbofish().with_args((#(#args,)*)) ))
             }
         }
-        expr => darling::Error::custom("faux::when! only accepts arguments in the format of: `when!(receiver.method)` or `receiver.method(args...)`")
-             .with_span(&expr)
-             .write_errors()
-             .into(),
+        expr => Err(darling::Error::custom(format!("faux::{}! only accepts arguments in the format of: `{}!(receiver.method)` or `receiver.method(args...)`", macro_name, macro_name))
+             .with_span(&expr)),
+    }
+}
+
+/// Converts `Type::function`, `path::function`, or either with
+/// `(args...)` to a call to the generated `_when_function` with the
+/// argument matchers
+fn associated_with_args(
+    qself: Option<syn::QSelf>,
+    mut path: syn::Path,
+    args: Vec<syn::Expr>,
+) -> darling::Result<proc_macro2::TokenStream> {
+    let function = path
+        .segments
+        .last_mut()
+        .expect("faux bug: paths have at least one segment");
+    function.ident = quote::format_ident!("_when_{}", function.ident);
+
+    let when = match qself {
+        // stubs are added to the type itself, even if the function
+        // belongs to a trait
+        Some(qself) => {
+            let ty = &qself.ty;
+            let function = &path.segments[path.segments.len() - 1];
+            quote! { <#ty>::#function(&mut faux::Associated::new()) }
+        }
+        None => quote! { #path(&mut faux::Associated::new()) },
+    };
+
+    if args.is_empty() {
+        Ok(when)
+    } else {
+        let args = args
+            .into_iter()
+            .map(expr_to_matcher)
+            .collect::<Result<Vec<_>, _>>()?;
+        Ok(quote! { #when.with_args((#(#args,)*)) })
     }
 }
 
@@ -1,4 +1,4 @@
-mod morphed;
+pub mod morphed;
 mod receiver;
 
 use crate::{create, self_type::SelfType};
@@ -12,6 +12,18 @@ use syn::PathArguments;
 pub struct Args {
     path: Option<syn::Path>,
     self_type: SelfType,
+    private: Private,
+}
+
+/// How private methods of a mock behave
+#[derive(FromMeta, PartialEq, Eq, Copy, Clone, Default)]
+#[darling(rename_all = "lowercase")]
+pub enum Private {
+    /// Calling them panics
+    #[default]
+    Panic,
+    /// They may be stubbed from within their module
+    Stub,
 }
 
 pub struct Mockable {
@@ -66,7 +78,9 @@ impl Mockable {
             let signature = Signature::morph(
                 &func.sig,
                 real.trait_.as_ref().map(|(_, path, _)| path),
+                &real.generics,
                 &func.vis,
+                args.private,
             );
             func.block = signature.create_body(args.self_type, &real_ty, &morphed_ty)?;
             if let Some(methods) = signature.create_when() {
@@ -1,4 +1,7 @@
-use crate::{methods::receiver::Receiver, self_type::SelfType};
+use crate::{
+    methods::{receiver::Receiver, Private},
+    self_type::SelfType,
+};
 use proc_macro2::TokenStream;
 use quote::{quote, ToTokens};
 use syn::{spanned::Spanned, Generics, Ident, PathArguments, Type, TypePath};
@@ -8,19 +11,24 @@ pub struct Signature<'a> {
     args: Vec<&'a syn::Pat>,
     is_async: bool,
     output: Option<&'a syn::Type>,
-    method_data: Option<MethodData<'a>>,
+    method_data: MethodData<'a>,
     trait_path: Option<&'a syn::Path>,
+    /// Whether the arguments own their data so the mock may keep them
+    keep_input: bool,
 }
 
 pub struct MethodData<'a> {
-    receiver: Receiver,
+    /// `None` for associated functions
+    receiver: Option<Receiver>,
     generics: syn::Generics,
     arg_types: Vec<WhenArg<'a>>,
     is_private: bool,
+    /// Whether the method is private but stubbable within its module
+    stub_private: bool,
 }
 
 #[derive(Debug)]
-pub struct WhenArg<'a>(&'a syn::Type);
+pub struct WhenArg<'a>(pub &'a syn::Type);
 
 pub fn has_impl_trait(ty: &syn::Type) -> bool {
     match ty {
@@ -87,7 +95,9 @@ impl<'a> Signature<'a> {
     pub fn morph(
         signature: &'a syn::Signature,
         trait_path: Option<&'a syn::Path>,
+        impl_generics: &syn::Generics,
         vis: &syn::Visibility,
+        private: Private,
     ) -> Signature<'a> {
         let receiver = Receiver::from_signature(signature);
         let generics = signature.generics.clone();
@@ -97,33 +107,42 @@ impl<'a> Signature<'a> {
             syn::ReturnType::Type(_, ty) => Some(ty.as_ref()),
         };
 
-        let method_data = receiver.map(|receiver| {
-            let arg_types = signature
-                .inputs
-                .iter()
-                .skip(1)
-                .map(|a| match a {
-                    syn::FnArg::Typed(arg) => WhenArg(&arg.ty),
-                    syn::FnArg::Receiver(_) => {
-                        unreachable!("this is a weird bug in faux if you reached this")
-                    }
-                })
-                .collect();
-
-            MethodData {
-                receiver,
-                generics,
-                arg_types,
-                is_private: trait_path.is_none() && *vis == syn::Visibility::Inherited,
-            }
-        });
+        let skipped = receiver.is_some() as usize;
+        let arg_types: Vec<_> = signature
+            .inputs
+            .iter()
+            .skip(skipped)
+            .map(|a| match a {
+                syn::FnArg::Typed(arg) => WhenArg(&arg.ty),
+                syn::FnArg::Receiver(_) => {
+                    unreachable!("this is a weird bug in faux if you reached this")
+                }
+            })
+            .collect();
+
+        let generics_in_scope: Vec<_> = impl_generics
+            .type_params()
+            .chain(generics.type_params())
+            .map(|tp| &tp.ident)
+            .collect();
+        let keep_input = arg_types
+            .iter()
+            .all(|ty| is_owned(ty.0, &generics_in_scope));
+
+        let method_data = MethodData {
+            receiver,
+            generics,
+            arg_types,
+            is_private: trait_path.is_none() && *vis == syn::Visibility::Inherited,
+            stub_private: private == Private::Stub,
+        };
 
         Signature {
             name: &signature.ident,
             args: signature
                 .inputs
                 .iter()
-                .skip(method_data.is_some() as usize)
+                .skip(skipped)
                 .map(|a| match a {
                     syn::FnArg::Typed(arg) => &*arg.pat,
                     syn::FnArg::Receiver(_) => {
@@ -135,6 +154,7 @@ impl<'a> Signature<'a> {
             output,
             method_data,
             trait_path,
+            keep_input,
         }
     }
 
@@ -147,12 +167,8 @@ impl<'a> Signature<'a> {
         let name = &self.name;
         let args = &self.args;
 
-        let generics = self
-            .method_data
-            .as_ref()
-            .map(|method_data| method_data.generics.clone());
-
-        let generic_idents = generic_type_idents(generics);
+        let method_data = &self.method_data;
+        let generic_idents = generic_type_idents(Some(method_data.generics.clone()));
         let turbofish = turbofish(&generic_idents);
 
         let proxy = match self.trait_path {
@@ -160,7 +176,7 @@ impl<'a> Signature<'a> {
             Some(path) => quote! { <#real_ty as #path>::#name #turbofish },
         };
 
-        let real_self_arg = self.method_data.as_ref().map(|_| {
+        let real_self_arg = method_data.receiver.as_ref().map(|_| {
             // need to pass the real Self arg to the real method
             syn::Pat::Ident(syn::PatIdent {
                 attrs: vec![],
@@ -181,61 +197,82 @@ impl<'a> Signature<'a> {
             proxy_real = wrapped_self;
         }
 
-        let ret = match &self.method_data {
-            // not stubbable
-            // proxy to real associated function
-            None => syn::parse2(proxy_real).unwrap(),
-            // else we can either proxy for real instances
-            // or call the mock store for faux instances
-            Some(method_data) => {
-                let call_stub = if method_data.is_private {
-                    quote! { panic!("faux error: private methods are not stubbable; and therefore not directly callable in a mock") }
-                } else {
-                    let faux_ident =
-                        syn::Ident::new(&format!("_faux_{}", name), proc_macro2::Span::call_site());
-
-                    let mut args =
-                        args.iter()
-                            .zip(method_data.arg_types.iter())
-                            .map(|(ident, ty)| {
-                                if has_impl_trait(ty.0) {
-                                    quote! {
-                                        std::boxed::Box::new(#ident)
-                                    }
-                                } else {
-                                    quote! { #ident }
-                                }
-                            });
-
-                    let args = if args.len() == 1 {
-                        let arg = args.next().unwrap();
-                        quote! { #arg }
-                    } else {
-                        quote! { (#(#args,)*) }
-                    };
+        let call_stub = if !method_data.is_stubbable() {
+            None
+        } else {
+            let faux_ident =
+                syn::Ident::new(&format!("_faux_{}", name), proc_macro2::Span::call_site());
 
-                    let fn_name = name.to_string();
-                    let mut generics_str = generic_idents
-                        .into_iter()
-                        .map(|i| i.to_string())
-                        .collect::<Vec<_>>()
-                        .join(",");
-                    generics_str.retain(|c| !c.is_whitespace());
-
-                    quote! {
-                        unsafe {
-                            match _maybe_faux_faux.call_stub(<Self>::#faux_ident #turbofish, #fn_name, #args, #generics_str) {
-                                std::result::Result::Ok(o) => o,
-                                std::result::Result::Err(e) => panic!("{}", e),
-                            }
+            let mut args = args
+                .iter()
+                .zip(method_data.arg_types.iter())
+                .map(|(ident, ty)| {
+                    if has_impl_trait(ty.0) {
+                        quote! {
+                            std::boxed::Box::new(#ident)
                         }
+                    } else {
+                        quote! { #ident }
                     }
-                };
+                });
 
-                method_data
-                    .receiver
-                    .method_body(real_self, proxy_real, call_stub)?
+            let args = if args.len() == 1 {
+                let arg = args.next().unwrap();
+                quote! { #arg }
+            } else {
+                quote! { (#(#args,)*) }
+            };
+            let describe_input = describe_input(&method_data.arg_types, self.keep_input);
+
+            let method_id = method_id(name, &generic_idents);
+            let mut generics_str = generic_idents
+                .iter()
+                .map(|i| i.to_string())
+                .collect::<Vec<_>>()
+                .join(",");
+            generics_str.retain(|c| !c.is_whitespace());
+
+            let call_stub = if self.is_async {
+                quote! { call_stub_async(<Self>::#faux_ident #turbofish, #method_id, _faux_input, _faux_describe, #generics_str).await }
+            } else {
+                quote! { call_stub(<Self>::#faux_ident #turbofish, #method_id, _faux_input, _faux_describe, #generics_str) }
+            };
+
+            Some(quote! {
+                unsafe {
+                    let _faux_input = faux::arg_debug::input(<Self>::#faux_ident #turbofish, #args);
+                    let _faux_describe = #describe_input;
+                    match _maybe_faux_faux.#call_stub {
+                        std::result::Result::Ok(o) => o,
+                        std::result::Result::Err(e) => panic!("{}", e),
+                    }
+                }
+            })
+        };
+
+        let ret = match (&method_data.receiver, call_stub) {
+            // we can either proxy for real instances
+            // or call the mock store for faux instances
+            (Some(receiver), call_stub) => {
+                let call_stub = call_stub.unwrap_or_else(|| {
+                    quote! { panic!("faux error: private methods are not stubbable; and therefore not directly callable in a mock") }
+                });
+                receiver.method_body(real_self, proxy_real, call_stub)?
+            }
+            // call the stubs of this thread if the associated
+            // function was stubbed, else proxy to the real one
+            (None, Some(call_stub)) => {
+                let method_id = method_id(name, &generic_idents);
+                syn::parse_quote! {
+                    match faux::Associated::stubbed::<Self>(#method_id) {
+                        std::option::Option::Some(_maybe_faux_faux) => #call_stub,
+                        std::option::Option::None => #proxy_real,
+                    }
+                }
             }
+            // not stubbable
+            // proxy to real associated function
+            (None, None) => syn::parse2(proxy_real).unwrap(),
         };
 
         Ok(syn::Block {
@@ -245,9 +282,8 @@ impl<'a> Signature<'a> {
     }
 
     pub fn create_when(&self) -> Option<Vec<syn::ImplItemFn>> {
-        self.method_data
-            .as_ref()
-            .filter(|m| !m.is_private)
+        Some(&self.method_data)
+            .filter(|m| m.is_stubbable())
             .map(|m| m.create_when(self.output, self.name))
     }
 
@@ -341,6 +377,10 @@ impl<'a> Signature<'a> {
 }
 
 impl MethodData<'_> {
+    fn is_stubbable(&self) -> bool {
+        !self.is_private || self.stub_private
+    }
+
     pub fn create_when(
         &self,
         output: Option<&syn::Type>,
@@ -350,9 +390,17 @@ impl MethodData<'_> {
             arg_types,
             receiver,
             generics,
+            is_private,
             ..
         } = self;
-        let receiver_ty = &receiver.ty;
+
+        // hooks of private methods are only visible within their
+        // module, where they may go unused
+        let vis = if *is_private {
+            quote! { #[allow(dead_code)] }
+        } else {
+            quote! { pub }
+        };
 
         let when_ident =
             syn::Ident::new(&format!("_when_{}", name), proc_macro2::Span::call_site());
@@ -361,46 +409,96 @@ impl MethodData<'_> {
 
         let empty = syn::parse_quote! { () };
         let output = output.unwrap_or(&empty);
-        let name_str = name.to_string();
-
-        let generics_contents = if generics.params.is_empty() {
-            None
-        } else {
-            let params = &generics.params;
-            Some(quote! { , #params })
-        };
-
         let generics_where_clause = &generics.where_clause;
 
         let generic_idents = generic_type_idents(Some(generics.clone()));
         let turbofish = turbofish(&generic_idents);
+        let method_id = method_id(name, &generic_idents);
+        let panic_message = format!("do not call this (<Self>::{})", faux_ident);
+
+        let receiver_ty = match receiver {
+            Some(receiver) => &receiver.ty,
+            None => {
+                // associated functions are stubbed on the current
+                // thread, with the stubs leased by `when!`
+                let when_method = syn::parse_quote! {
+                    #[track_caller]
+                    #vis fn #when_ident #generics (_faux_stubs: &mut faux::Associated) -> faux::When<'_, (), (#(#arg_types),*), #output, faux::matcher::AnyInvocation> #generics_where_clause {
+                        faux::When::new(
+                            <Self>::#faux_ident #turbofish,
+                            #method_id,
+                            false,
+                            _faux_stubs.faux::<Self>()
+                        )
+                    }
+                };
+
+                let faux_method = syn::parse_quote! {
+                    #[allow(clippy::boxed_local)]
+                    #vis fn #faux_ident #generics (_: (), _: (#(#arg_types),*)) -> #output #generics_where_clause {
+                        panic!(#panic_message)
+                    }
+                };
+
+                return vec![when_method, faux_method];
+            }
+        };
+        let borrows_receiver = output_borrows_receiver(receiver_ty, output);
+        let arg_tys: Vec<_> = arg_types.iter().map(|ty| ty.0).collect();
+        let new_when = quote! {
+            (
+                <Self>::#faux_ident #turbofish,
+                #method_id,
+                #borrows_receiver,
+                _maybe_faux_faux
+            )
+        };
+        // methods that may be stubbed in a scope witness their arguments
+        // as written, see `When::new_scopable`
+        let (witness, new_when) = if scopable(receiver_ty, generics, &arg_tys, output) {
+            (
+                quote! { , fn((#(#arg_types),*)) },
+                quote! { unsafe { faux::When::new_scopable #new_when } },
+            )
+        } else {
+            (quote! {}, quote! { faux::When::new #new_when })
+        };
 
         let when_method = syn::parse_quote! {
-            pub fn #when_ident<'m #generics_contents>(&'m mut self) -> faux::When<'m, #receiver_ty, (#(#arg_types),*), #output, faux::matcher::AnyInvocation> #generics_where_clause {
+            #[track_caller]
+            #vis fn #when_ident #generics (&mut self) -> faux::When<'_, #receiver_ty, (#(#arg_types),*), #output, faux::matcher::AnyInvocation #witness> #generics_where_clause {
                 match &mut self.0 {
-                    faux::MaybeFaux::Faux(_maybe_faux_faux) => faux::When::new(
-                        <Self>::#faux_ident #turbofish,
-                        #name_str,
-                        _maybe_faux_faux
-                    ),
+                    faux::MaybeFaux::Faux(_maybe_faux_faux) => #new_when,
                     faux::MaybeFaux::Real(_) => panic!("not allowed to stub a real instance!"),
                 }
             }
         };
 
-        let proxy = quote! { <Self>::#faux_ident #turbofish };
+        let calls_ident =
+            syn::Ident::new(&format!("_calls_{}", name), proc_macro2::Span::call_site());
 
-        let panic_message = format!("do not call this ({proxy})");
+        let calls_method = syn::parse_quote! {
+            #vis fn #calls_ident #generics (&self) -> faux::Calls<'_, #receiver_ty, (#(#arg_types),*), #output> #generics_where_clause {
+                match &self.0 {
+                    faux::MaybeFaux::Faux(_maybe_faux_faux) => faux::Calls::new(
+                        <Self>::#faux_ident #turbofish,
+                        #method_id,
+                        _maybe_faux_faux
+                    ),
+                    faux::MaybeFaux::Real(_) => panic!("not allowed to wait for calls to a real instance!"),
+                }
+            }
+        };
 
         let faux_method = syn::parse_quote! {
             #[allow(clippy::needless_arbitrary_self_type)]
             #[allow(clippy::boxed_local)]
-            pub fn #faux_ident #generics (self: #receiver_ty, _: (#(#arg_types),*)) -> #output #generics_where_clause {
-                panic!(concat!(#panic_message, "{:?}"), #proxy as *const ())
+            #vis fn #faux_ident #generics (self: #receiver_ty, _: (#(#arg_types),*)) -> #output #generics_where_clause {
+                panic!(#panic_message)
             }
         };
 
-        vec![when_method, faux_method]
+        vec![when_method, calls_method, faux_method]
     }
 }
 
@@ -500,13 +598,181 @@ fn path_args_contains_self(path: &syn::Path, self_path: &syn::TypePath) -> bool
     }
 }
 
-fn generic_type_idents(generics: Option<Generics>) -> Vec<Ident> {
+pub fn generic_type_idents(generics: Option<Generics>) -> Vec<Ident> {
     generics
         .map(|g| g.type_params().map(|tp| tp.ident.clone()).collect())
         .unwrap_or_default()
 }
 
-fn turbofish(idents: &[Ident]) -> TokenStream {
+/// Describes `_faux_input`, made of arguments of `arg_types`, to its
+/// mock
+///
+/// The arguments are only formatted if the invocation fails. They are
+/// kept to be listed by later failures if `keep` and they are `Clone
+/// + Send + Sync + 'static`.
+pub fn describe_input(arg_types: &[WhenArg], keep: bool) -> TokenStream {
+    let arg_count = arg_types.len();
+    let args_debug = (0..arg_count).map(|i| {
+        if arg_count == 1 {
+            quote! { (&faux::arg_debug::Arg(_faux_input)).faux_debug() }
+        } else {
+            let i = syn::Index::from(i);
+            quote! { (&faux::arg_debug::Arg(&_faux_input.#i)).faux_debug() }
+        }
+    });
+    let keep = if keep {
+        quote! {{
+            use faux::arg_debug::{NotKept as _, ViaClone as _};
+            // elided lifetimes are higher-ranked in the witness so
+            // inputs that may borrow are not kept
+            let witness = std::marker::PhantomData::<fn((#(#arg_types),*))>;
+            (&faux::arg_debug::Keep(&_faux_input, witness)).faux_keeper()
+        }}
+    } else {
+        quote! { std::option::Option::None }
+    };
+
+    quote! {
+        faux::arg_debug::Input::new(
+            &_faux_input,
+            |_faux_input| {
+                use faux::arg_debug::{ViaDebug as _, ViaPlaceholder as _};
+                faux::arg_debug::format(&[#(#args_debug),*])
+            },
+            #keep,
+        )
+    }
+}
+
+/// Whether the type owns all of its data, so that its values may be
+/// kept past the invocation they were given to
+///
+/// This is only a syntactic check: references, lifetimes, `impl
+/// Trait`, `Self`, macros and the `generics` in scope may all borrow
+/// data. Lifetimes elided from a path, such as in `Cow<str>`, cannot
+/// be seen and are caught by the witness of [`describe_input`].
+pub fn is_owned(ty: &syn::Type, generics: &[&Ident]) -> bool {
+    fn owned_tokens(tokens: TokenStream, generics: &[&Ident]) -> bool {
+        tokens.into_iter().all(|token| match token {
+            proc_macro2::TokenTree::Group(group) => owned_tokens(group.stream(), generics),
+            proc_macro2::TokenTree::Punct(punct) => !matches!(punct.as_char(), '&' | '\'' | '!'),
+            proc_macro2::TokenTree::Ident(ident) => {
+                ident != "impl" && ident != "Self" && !generics.iter().any(|g| ident == **g)
+            }
+            proc_macro2::TokenTree::Literal(_) => true,
+        })
+    }
+
+    owned_tokens(ty.to_token_stream(), generics)
+}
+
+/// Whether the output is a reference that may only borrow from the
+/// receiver, so the mock may return references to values it keeps
+/// alive
+fn output_borrows_receiver(receiver: &syn::Type, output: &syn::Type) -> bool {
+    let (receiver, output) = match (receiver, output) {
+        (syn::Type::Reference(receiver), syn::Type::Reference(output)) => (receiver, output),
+        _ => return false,
+    };
+
+    // elided lifetimes of the output are the lifetime of the receiver
+    match (&output.lifetime, &receiver.lifetime) {
+        (None, _) => true,
+        (Some(output), _) if output.ident == "_" => true,
+        (Some(output), Some(receiver)) => output == receiver,
+        (Some(_), None) => false,
+    }
+}
+
+/// Whether the stubs of the method may borrow from a `faux::scope`
+///
+/// A stub is stored with the lifetimes that `when!` inferred for it,
+/// so every call must be unable to pick other lifetimes for data the
+/// stub may keep or hand out. Only the receiver's lifetime, `'static`
+/// and lifetimes of the impl may appear in the output, and no
+/// lifetimes or generic types of the method in the arguments or the
+/// output. Elided lifetimes of the arguments are caught by the
+/// witness given to `When::new_scopable` instead, since they may be
+/// hidden in a path.
+fn scopable(
+    receiver: &syn::Type,
+    generics: &syn::Generics,
+    args: &[&syn::Type],
+    output: &syn::Type,
+) -> bool {
+    let method_lifetimes: Vec<_> = generics.lifetimes().map(|l| &l.lifetime.ident).collect();
+    let method_types: Vec<_> = generics.type_params().map(|t| &t.ident).collect();
+    let receiver_lifetime = match receiver {
+        syn::Type::Reference(receiver) => Some(receiver.lifetime.as_ref().map(|l| &l.ident)),
+        _ => None,
+    };
+
+    let args_scopable = args.iter().all(|ty| {
+        let borrows = Borrows::of(ty, &method_types);
+        !borrows.generic
+            && borrows
+                .lifetimes
+                .iter()
+                .all(|l| !method_lifetimes.contains(&l))
+    });
+
+    let borrows = Borrows::of(output, &method_types);
+    let output_scopable = !borrows.generic
+        && borrows
+            .lifetimes
+            .iter()
+            .all(|l| !method_lifetimes.contains(&l) || receiver_lifetime == Some(Some(l)));
+
+    args_scopable && output_scopable
+}
+
+/// What a type may borrow, found by looking at its tokens
+struct Borrows {
+    /// Named lifetimes, without their `'`
+    lifetimes: Vec<Ident>,
+    /// Whether it names one of the given generic types
+    generic: bool,
+}
+
+impl Borrows {
+    fn of(ty: &syn::Type, generics: &[&Ident]) -> Self {
+        fn walk(tokens: TokenStream, generics: &[&Ident], borrows: &mut Borrows) {
+            let mut tokens = tokens.into_iter();
+            while let Some(token) = tokens.next() {
+                match token {
+                    proc_macro2::TokenTree::Group(group) => walk(group.stream(), generics, borrows),
+                    proc_macro2::TokenTree::Punct(punct) if punct.as_char() == '\'' => {
+                        if let Some(proc_macro2::TokenTree::Ident(lifetime)) = tokens.next() {
+                            borrows.lifetimes.push(lifetime);
+                        }
+                    }
+                    proc_macro2::TokenTree::Ident(ident) => {
+                        borrows.generic |= generics.iter().any(|g| ident == **g);
+                    }
+                    _ => {}
+                }
+            }
+        }
+
+        let mut borrows = Borrows {
+            lifetimes: vec![],
+            generic: false,
+        };
+        walk(ty.to_token_stream(), generics, &mut borrows);
+        borrows
+    }
+}
+
+/// Identifies the method within its mock store, one per instantiation
+/// of its generic type parameters
+pub fn method_id(name: &Ident, generic_idents: &[Ident]) -> TokenStream {
+    let name = name.to_string();
+    quote! {
+        faux::MethodId::new::<(#(std::marker::PhantomData<#generic_idents>,)*)>(#name)
+    }
+}
+
+pub fn turbofish(idents: &[Ident]) -> TokenStream {
     if idents.is_empty() {
         quote! {}
     } else {
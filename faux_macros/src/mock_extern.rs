@@ -0,0 +1,260 @@
+use crate::create;
+use quote::quote;
+use syn::parse::{Parse, ParseStream};
+
+pub struct Mockable {
+    // the declared struct, wrapping the foreign type
+    real: syn::ItemStruct,
+    // the declared impl blocks
+    impls: Vec<Impl>,
+}
+
+struct Impl {
+    attrs: Vec<syn::Attribute>,
+    trait_: Option<syn::Path>,
+    self_ty: syn::Ident,
+    methods: Vec<Method>,
+}
+
+struct Method {
+    attrs: Vec<syn::Attribute>,
+    vis: syn::Visibility,
+    sig: syn::Signature,
+}
+
+impl Parse for Mockable {
+    fn parse(input: ParseStream) -> syn::Result<Self> {
+        let real: syn::ItemStruct = input.parse()?;
+        let mut impls = vec![];
+        while !input.is_empty() {
+            impls.push(input.parse()?);
+        }
+
+        Ok(Mockable { real, impls })
+    }
+}
+
+impl Parse for Impl {
+    fn parse(input: ParseStream) -> syn::Result<Self> {
+        let attrs = input.call(syn::Attribute::parse_outer)?;
+        input.parse::<syn::Token![impl]>()?;
+
+        let trait_ = if input.peek2(syn::token::Brace) {
+            None
+        } else {
+            let path = input.parse()?;
+            input.parse::<syn::Token![for]>()?;
+            Some(path)
+        };
+        let self_ty = input.parse()?;
+
+        let content;
+        syn::braced!(content in input);
+        let mut methods = vec![];
+        while !content.is_empty() {
+            methods.push(Method {
+                attrs: content.call(syn::Attribute::parse_outer)?,
+                vis: content.parse()?,
+                sig: content.parse()?,
+            });
+            content.parse::<syn::Token![;]>()?;
+        }
+
+        Ok(Impl {
+            attrs,
+            trait_,
+            self_ty,
+            methods,
+        })
+    }
+}
+
+impl Mockable {
+    pub fn validate(&self) -> darling::Result<()> {
+        let mut errors = darling::Error::accumulator();
+
+        if !self.real.generics.params.is_empty() {
+            errors.push(
+                darling::Error::custom("faux::mock_extern! does not support generic structs")
+                    .with_span(&self.real.generics),
+            );
+        }
+
+        if !matches!(&self.real.fields, syn::Fields::Unnamed(fields) if fields.unnamed.len() == 1) {
+            errors.push(darling::Error::custom("faux::mock_extern! expects a struct wrapping the foreign type: `pub struct Client(foreign::Client);`").with_span(&self.real.fields));
+        }
+
+        for block in &self.impls {
+            if block.self_ty != self.real.ident {
+                errors.push(
+                    darling::Error::custom(format!(
+                        "faux::mock_extern! may only declare impl blocks for `{}`",
+                        self.real.ident
+                    ))
+                    .with_span(&block.self_ty),
+                );
+            }
+
+            for method in &block.methods {
+                if let Some(receiver) = method.sig.receiver() {
+                    if receiver.colon_token.is_some() {
+                        errors.push(darling::Error::custom("faux::mock_extern! only supports `self`, `&self`, and `&mut self` receivers").with_span(receiver));
+                    }
+                }
+
+                method
+                    .sig
+                    .inputs
+                    .iter()
+                    .filter_map(|arg| match arg {
+                        syn::FnArg::Typed(arg) => Some(arg),
+                        syn::FnArg::Receiver(_) => None,
+                    })
+                    .filter(|arg| !matches!(*arg.pat, syn::Pat::Ident(_)))
+                    .for_each(|arg| {
+                        errors.push(
+                            darling::Error::custom(
+                                "faux::mock_extern! expects every argument to be named",
+                            )
+                            .with_span(&arg.pat),
+                        )
+                    });
+            }
+        }
+
+        errors.finish()
+    }
+}
+
+impl From<Mockable> for proc_macro::TokenStream {
+    fn from(mockable: Mockable) -> Self {
+        let Mockable { real, impls } = mockable;
+
+        let name = &real.ident;
+        let real_name = create::real_struct_new_ident(name);
+        let foreign = match &real.fields {
+            syn::Fields::Unnamed(fields) => &fields.unnamed[0].ty,
+            _ => unreachable!("validated to wrap a single type"),
+        };
+        // referred to by the real implementations, which are nested in a
+        // mod that may not resolve relative paths to the foreign type
+        let foreign_alias = quote::format_ident!("_FauxForeign_{}", name);
+
+        let impls = impls.into_iter().map(|block| {
+            let Impl {
+                attrs,
+                trait_,
+                self_ty,
+                methods,
+            } = block;
+
+            let methods: Vec<_> = methods
+                .into_iter()
+                .map(|method| {
+                    let Method { attrs, vis, sig } = method;
+                    let vis = match (vis, &trait_) {
+                        // every declared method is mockable
+                        (syn::Visibility::Inherited, None) => syn::parse_quote! { pub },
+                        (vis, _) => vis,
+                    };
+                    let body = delegate(&sig, trait_.as_ref(), &foreign_alias);
+
+                    quote! {
+                        #(#attrs)*
+                        #vis #sig {
+                            #body
+                        }
+                    }
+                })
+                .collect();
+            let trait_ = trait_.map(|path| quote! { #path for });
+
+            quote! {
+                #[faux::methods]
+                #(#attrs)*
+                impl #trait_ #self_ty {
+                    #(#methods)*
+                }
+            }
+        });
+
+        proc_macro::TokenStream::from(quote! {
+            #[faux::create]
+            #real
+
+            #[allow(non_camel_case_types)]
+            type #foreign_alias = #foreign;
+
+            impl From<#foreign> for #name {
+                fn from(real: #foreign) -> Self {
+                    Self(faux::MaybeFaux::Real(#real_name(real)))
+                }
+            }
+
+            #(#impls)*
+        })
+    }
+}
+
+/// Calls the method of the same name on the foreign type
+fn delegate(
+    sig: &syn::Signature,
+    trait_: Option<&syn::Path>,
+    foreign: &syn::Ident,
+) -> proc_macro2::TokenStream {
+    let ident = &sig.ident;
+    let args = sig.inputs.iter().filter_map(|arg| match arg {
+        syn::FnArg::Typed(syn::PatType { pat, .. }) => match pat.as_ref() {
+            syn::Pat::Ident(pat) => Some(&pat.ident),
+            _ => None,
+        },
+        syn::FnArg::Receiver(_) => None,
+    });
+
+    let call = match (sig.receiver(), trait_) {
+        (Some(_), None) => quote! { self.0.#ident(#(#args),*) },
+        (Some(receiver), Some(trait_)) => {
+            let foreign_self = match (&receiver.reference, &receiver.mutability) {
+                (None, _) => quote! { self.0 },
+                (Some(_), None) => quote! { &self.0 },
+                (Some(_), Some(_)) => quote! { &mut self.0 },
+            };
+            quote! { <#foreign as #trait_>::#ident(#foreign_self, #(#args),*) }
+        }
+        (None, None) => quote! { #foreign::#ident(#(#args),*) },
+        (None, Some(trait_)) => quote! { <#foreign as #trait_>::#ident(#(#args),*) },
+    };
+    let call = match sig.asyncness {
+        Some(_) => quote! { #call.await },
+        None => call,
+    };
+
+    let output = match &sig.output {
+        syn::ReturnType::Type(_, ty) => match ty.as_ref() {
+            syn::Type::Path(output) => output,
+            _ => return call,
+        },
+        syn::ReturnType::Default => return call,
+    };
+    if output.path.is_ident("Self") {
+        return quote! { Self(#call) };
+    }
+
+    // wrap the foreign type the same way `#[faux::methods]` wraps `Self`
+    let last = output.path.segments.last().unwrap();
+    let wraps_self = match &last.arguments {
+        syn::PathArguments::AngleBracketed(args) => matches!(
+            args.args.first(),
+            Some(syn::GenericArgument::Type(syn::Type::Path(arg))) if arg.path.is_ident("Self")
+        ),
+        _ => false,
+    };
+    if !wraps_self {
+        return call;
+    }
+    match last.ident.to_string().as_str() {
+        "Result" | "Option" => quote! { #call.map(Self) },
+        "Box" => quote! { std::boxed::Box::new(Self(*#call)) },
+        _ => call,
+    }
+}
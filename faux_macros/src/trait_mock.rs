@@ -0,0 +1,124 @@
+use darling::FromMeta;
+use quote::quote;
+
+#[derive(Default, FromMeta)]
+#[darling(default)]
+pub struct Args {
+    name: Option<syn::Ident>,
+}
+
+pub struct Mockable {
+    // the trait definition, left untouched
+    real: syn::ItemTrait,
+    // the name of the struct that mocks the trait
+    name: syn::Ident,
+    // the signatures of the methods to mock
+    methods: Vec<syn::Signature>,
+}
+
+impl Mockable {
+    pub fn new(real: syn::ItemTrait, args: Args) -> darling::Result<Self> {
+        if !real.generics.params.is_empty() {
+            return Err(darling::Error::custom(
+                "#[faux::trait_mock] does not support generic traits",
+            )
+            .with_span(&real.generics));
+        }
+
+        let mut errors = darling::Error::accumulator();
+        let methods = real
+            .items
+            .iter()
+            .filter_map(|item| match item {
+                syn::TraitItem::Fn(func) if func.sig.receiver().is_some() => {
+                    Some(func.sig.clone())
+                }
+                syn::TraitItem::Fn(func) => {
+                    errors.push(darling::Error::custom("#[faux::trait_mock] does not support associated functions without a receiver").with_span(&func.sig));
+                    None
+                }
+                syn::TraitItem::Type(ty) => {
+                    errors.push(
+                        darling::Error::custom(
+                            "#[faux::trait_mock] does not support associated types",
+                        )
+                        .with_span(ty),
+                    );
+                    None
+                }
+                syn::TraitItem::Const(constant) => {
+                    errors.push(
+                        darling::Error::custom(
+                            "#[faux::trait_mock] does not support associated constants",
+                        )
+                        .with_span(constant),
+                    );
+                    None
+                }
+                _ => None,
+            })
+            .map(ignore_args)
+            .collect();
+        errors.finish()?;
+
+        let name = args
+            .name
+            .unwrap_or_else(|| quote::format_ident!("Mock{}", real.ident));
+
+        Ok(Mockable {
+            real,
+            name,
+            methods,
+        })
+    }
+}
+
+impl From<Mockable> for proc_macro::TokenStream {
+    fn from(mockable: Mockable) -> Self {
+        let Mockable {
+            real,
+            name,
+            methods,
+        } = mockable;
+
+        let vis = &real.vis;
+        let trait_ident = &real.ident;
+        let unsafety = &real.unsafety;
+        let doc = format!(
+            " Mock of [`{}`] created by `#[faux::trait_mock]`.",
+            trait_ident
+        );
+
+        proc_macro::TokenStream::from(quote! {
+            #real
+
+            #[doc = #doc]
+            #[faux::create]
+            #vis struct #name {}
+
+            #[faux::methods]
+            #unsafety impl #trait_ident for #name {
+                #(#methods {
+                    unreachable!("faux: mocks created by #[faux::trait_mock] have no real implementation")
+                })*
+            }
+        })
+    }
+}
+
+/// Replaces the patterns of the arguments with `_` since the real
+/// implementation never uses them
+fn ignore_args(mut signature: syn::Signature) -> syn::Signature {
+    signature
+        .inputs
+        .iter_mut()
+        .filter_map(|arg| match arg {
+            syn::FnArg::Receiver(_) => None,
+            syn::FnArg::Typed(arg) => Some(arg),
+        })
+        .for_each(|arg| {
+            arg.attrs = vec![];
+            *arg.pat = syn::parse_quote! { _ };
+        });
+    signature
+}
@@ -0,0 +1,126 @@
+//! Formats the arguments of an invocation without requiring them to
+//! implement [`Debug`](fmt::Debug), and keeps them if they own their
+//! data.
+//!
+//! Used by the code generated by [`#[methods]`](crate::methods) to
+//! record invocations. Arguments that implement `Debug` are printed
+//! using it, the rest are printed as a placeholder. This relies on
+//! autoref-based specialization so it only works on concrete types
+//! or on generic types bound by `Debug`.
+
+use std::{
+    fmt::{self, Formatter},
+    marker::PhantomData,
+};
+
+/// Describes the input of an invocation to its mock
+///
+/// Nothing is formatted unless the invocation fails, so calls that
+/// find a stub do not pay for it.
+pub struct Input<I> {
+    /// Formats the input as a comma separated list of arguments
+    pub format: fn(&I) -> String,
+    /// Clones the input to be listed by later failures, `None` if it
+    /// may borrow data
+    pub keep: Option<fn(&I) -> I>,
+}
+
+impl<I> Input<I> {
+    /// `input` is only used to infer the type of the input
+    pub fn new(_input: &I, format: fn(&I) -> String, keep: Option<fn(&I) -> I>) -> Self {
+        Input { format, keep }
+    }
+}
+
+/// Passes `input` through as the input type of the method `_id`, so
+/// `impl Trait` arguments are coerced to the trait objects the mock
+/// takes
+pub fn input<R, I, O>(_id: fn(R, I) -> O, input: I) -> I {
+    input
+}
+
+impl<I> Clone for Input<I> {
+    fn clone(&self) -> Self {
+        *self
+    }
+}
+
+impl<I> Copy for Input<I> {}
+
+/// Wraps an argument to be formatted.
+///
+/// Call `(&Arg(&arg)).faux_debug()` with both [`ViaDebug`] and
+/// [`ViaPlaceholder`] in scope.
+pub struct Arg<'a, T>(pub &'a T);
+
+/// Picked for arguments that implement `Debug`
+pub trait ViaDebug<'a> {
+    fn faux_debug(&self) -> &'a dyn fmt::Debug;
+}
+
+impl<'a, T: fmt::Debug> ViaDebug<'a> for Arg<'a, T> {
+    fn faux_debug(&self) -> &'a dyn fmt::Debug {
+        self.0
+    }
+}
+
+/// Picked for arguments that do not implement `Debug`
+pub trait ViaPlaceholder {
+    fn faux_debug(&self) -> &'static dyn fmt::Debug;
+}
+
+impl<T> ViaPlaceholder for &Arg<'_, T> {
+    fn faux_debug(&self) -> &'static dyn fmt::Debug {
+        &Placeholder
+    }
+}
+
+struct Placeholder;
+
+impl fmt::Debug for Placeholder {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        f.write_str("<?>")
+    }
+}
+
+/// Formats the arguments as a comma separated list
+pub fn format(args: &[&dyn fmt::Debug]) -> String {
+    args.iter()
+        .map(|a| format!("{:?}", a))
+        .collect::<Vec<_>>()
+        .join(", ")
+}
+
+/// Wraps the input of an invocation to be kept.
+///
+/// Call `(&Keep(&input, witness)).faux_keeper()` with both
+/// [`ViaClone`] and [`NotKept`] in scope, where `witness` is a
+/// `PhantomData<fn(I)>` of the input type `I` as written in the
+/// signature. Its elided lifetimes are higher-ranked, so inputs that
+/// may borrow data fall back to `NotKept` instead of failing to
+/// compile for not being `'static`. Named lifetimes are not, so it is
+/// only generated for inputs whose types have no lifetimes nor
+/// generics that can be seen.
+pub struct Keep<'a, T, W>(pub &'a T, pub PhantomData<W>);
+
+/// Picked for inputs that may be cloned and shared across threads
+pub trait ViaClone<T> {
+    fn faux_keeper(&self) -> Option<fn(&T) -> T>;
+}
+
+impl<T: Clone + Send + Sync + 'static> ViaClone<T> for Keep<'_, T, fn(T)> {
+    fn faux_keeper(&self) -> Option<fn(&T) -> T> {
+        Some(T::clone)
+    }
+}
+
+/// Picked for inputs that cannot be kept
+pub trait NotKept<T> {
+    fn faux_keeper(&self) -> Option<fn(&T) -> T>;
+}
+
+impl<T, W> NotKept<T> for &Keep<'_, T, W> {
+    fn faux_keeper(&self) -> Option<fn(&T) -> T> {
+        None
+    }
+}
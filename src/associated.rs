@@ -0,0 +1,227 @@
+use std::{
+    any::{self, TypeId},
+    cell::RefCell,
+    collections::HashMap,
+    marker::PhantomData,
+    sync::Arc,
+    thread,
+};
+
+use crate::{
+    mock::{type_id, MethodId},
+    Faux,
+};
+
+thread_local! {
+    /// Stubs of the associated functions of each mockable struct and
+    /// of the functions of each module
+    static STUBS: RefCell<HashMap<Key, Faux>> = RefCell::new(HashMap::new());
+}
+
+/// Whose stubs are registered
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+enum Key {
+    /// The associated functions of the struct of this type
+    ///
+    /// Type names are only used in messages since they are not
+    /// unique.
+    Struct(TypeId),
+    /// The functions of the module at this path
+    Module(&'static str),
+}
+
+/// Lease on the stubs of the associated functions of a mockable
+/// struct, or of the functions of a module, on the current thread
+///
+/// Created by [`when!`](crate::when!) when stubbing an associated
+/// function or a [`#[function]`](crate::function). The stubs are taken out of the thread's registry while
+/// they are being added to, so calls to the associated functions in
+/// the meantime use their real implementation.
+#[doc(hidden)]
+#[derive(Debug, Default)]
+pub struct Associated {
+    leased: Option<(Key, Faux)>,
+}
+
+impl Associated {
+    pub fn new() -> Self {
+        Associated::default()
+    }
+
+    /// The stubs of the associated functions of `T`, taken out of the
+    /// registry until the lease is dropped
+    pub fn faux<T: ?Sized>(&mut self) -> &mut Faux {
+        let name = struct_name(any::type_name::<T>());
+        self.lease(Key::Struct(type_id::of::<T>()), name)
+    }
+
+    /// The stubs of the functions of the module at `module_path`,
+    /// taken out of the registry until the lease is dropped
+    pub fn functions(&mut self, module_path: &'static str) -> &mut Faux {
+        self.lease(Key::Module(module_path), module_path)
+    }
+
+    /// A handle to the stubs of the associated functions of `T` if
+    /// `method` was stubbed on this thread
+    pub fn stubbed<T: ?Sized>(method: MethodId) -> Option<Faux> {
+        Associated::handle(Key::Struct(type_id::of::<T>()), method)
+    }
+
+    /// A handle to the stubs of the functions of the module at
+    /// `module_path` if `function` was stubbed on this thread
+    pub fn stubbed_function(module_path: &'static str, function: MethodId) -> Option<Faux> {
+        Associated::handle(Key::Module(module_path), function)
+    }
+
+    fn lease(&mut self, key: Key, name: &'static str) -> &mut Faux {
+        let (_, faux) = self.leased.get_or_insert_with(|| {
+            let faux = STUBS
+                .with(|stubs| stubs.borrow_mut().remove(&key))
+                .unwrap_or_else(|| Faux::registered(name));
+            (key, faux)
+        });
+        faux
+    }
+
+    fn handle(key: Key, method: MethodId) -> Option<Faux> {
+        STUBS.with(|stubs| {
+            stubs
+                .borrow()
+                .get(&key)
+                .filter(|faux| faux.store.is_stubbed(method))
+                .map(|faux| Faux {
+                    store: Arc::clone(&faux.store),
+                })
+        })
+    }
+}
+
+impl Drop for Associated {
+    fn drop(&mut self) {
+        if let Some((key, faux)) = self.leased.take() {
+            // the registry is gone if the thread is exiting
+            let _ = STUBS.try_with(|stubs| stubs.borrow_mut().insert(key, faux));
+        }
+    }
+}
+
+/// Removes the stubs of associated functions and of
+/// [`#[function]`](crate::function)s created on the current thread.
+///
+/// The stubs are kept until the thread exits otherwise, without
+/// checking whether they were called as many times as expected: a
+/// panic while the thread exits would abort the process. Call this at
+/// the end of a test, or hold a [`StubsGuard`], to check them.
+///
+/// # Panics
+///
+/// Panics if any of the removed stubs was called fewer times than
+/// expected, unless the thread is already panicking.
+///
+/// # Examples
+///
+/// ```should_panic
+/// #[faux::create]
+/// pub struct Connection {}
+///
+/// #[faux::methods]
+/// impl Connection {
+///     pub fn connect(addr: &str) -> Result<Self, String> {
+///         /* implementation code */
+///         # panic!()
+///     }
+/// }
+///
+/// # fn main() {
+/// faux::when!(Connection::connect(_))
+///     .at_least(1)
+///     .then(|_| Err("down".to_string()));
+///
+/// // panics: `connect` expected at least 1 call but was never called
+/// faux::clear_stubs();
+/// # }
+/// ```
+pub fn clear_stubs() {
+    let removed = STUBS
+        .try_with(|stubs| std::mem::take(&mut *stubs.borrow_mut()))
+        .unwrap_or_default();
+    if thread::panicking() {
+        return;
+    }
+
+    let mut unmet: Vec<_> = removed
+        .values()
+        .filter_map(|faux| faux.store.unmet_report())
+        .collect();
+    if unmet.is_empty() {
+        return;
+    }
+    unmet.sort();
+    panic!("{}", unmet.join("\n"));
+}
+
+/// Removes the stubs of associated functions and of
+/// [`#[function]`](crate::function)s created on the current thread
+/// once dropped.
+///
+/// Hold one for the duration of a test so its stubs are checked, and
+/// do not leak into later tests on the same thread. See
+/// [`clear_stubs`].
+///
+/// # Examples
+///
+/// ```
+/// #[faux::create]
+/// pub struct Connection {}
+///
+/// #[faux::methods]
+/// impl Connection {
+///     pub fn connect(addr: &str) -> Result<Self, String> {
+///         /* implementation code */
+///         # panic!()
+///     }
+/// }
+///
+/// # fn main() {
+/// let _stubs = faux::StubsGuard::new();
+/// faux::when!(Connection::connect(_))
+///     .at_least(1)
+///     .then(|_| Err("down".to_string()));
+///
+/// assert!(Connection::connect("localhost").is_err());
+/// // the stub is checked and removed once `_stubs` is dropped
+/// # }
+/// ```
+#[must_use = "the stubs are removed once the guard is dropped"]
+#[derive(Debug)]
+pub struct StubsGuard {
+    // the registry is per thread so the guard must stay on it
+    _thread: PhantomData<*const ()>,
+}
+
+impl StubsGuard {
+    pub fn new() -> Self {
+        StubsGuard {
+            _thread: PhantomData,
+        }
+    }
+}
+
+impl Default for StubsGuard {
+    fn default() -> Self {
+        StubsGuard::new()
+    }
+}
+
+impl Drop for StubsGuard {
+    /// Panics if a removed stub was called fewer times than expected
+    fn drop(&mut self) {
+        clear_stubs()
+    }
+}
+
+/// Name of the struct without its path or generics
+fn struct_name(type_name: &'static str) -> &'static str {
+    let path = type_name.split('<').next().unwrap_or(type_name);
+    path.rsplit("::").next().unwrap_or(path)
+}
@@ -0,0 +1,132 @@
+//! Tools to wait for the invocations of your mocks.
+
+use std::{
+    future::Future,
+    pin::Pin,
+    sync::{
+        atomic::{AtomicUsize, Ordering},
+        Arc,
+    },
+    task::{Context, Poll},
+    time::Duration,
+};
+
+use crate::{
+    matcher::InvocationMatcher,
+    mock::{MethodId, Mock},
+    Faux,
+};
+
+/// Waits for a method of a mock to be called a number of times.
+///
+/// Created using [`await_calls!`], which is the preferred way to use
+/// it.
+///
+/// By default, all calls to the method are counted, including the
+/// ones made before waiting. Argument matchers are checked as calls
+/// are made, and only while someone waits on them, so when they are
+/// given only the matching calls made after the wait starts are
+/// counted.
+///
+/// Do *NOT* rely on the signature of `Calls`. While changing the
+/// methods of `Calls` will be considered a breaking change, changing
+/// the generics within `Calls` will not.
+///
+/// [`await_calls!`]: crate::await_calls!
+pub struct Calls<'m, R, I, O> {
+    id: fn(R, I) -> O,
+    method: MethodId,
+    faux: &'m Faux,
+    matcher: Option<Box<dyn InvocationMatcher<I> + Send>>,
+}
+
+impl<'m, R, I, O> Calls<'m, R, I, O> {
+    #[doc(hidden)]
+    pub fn new(id: fn(R, I) -> O, method: MethodId, faux: &'m Faux) -> Self {
+        Calls {
+            id,
+            method,
+            faux,
+            matcher: None,
+        }
+    }
+
+    /// Only counts the calls that match the given matcher.
+    ///
+    /// See [`When::with_args`](crate::When::with_args) for the
+    /// matchers that may be passed.
+    pub fn with_args(mut self, matcher: impl InvocationMatcher<I> + Send + 'static) -> Self {
+        self.matcher = Some(Box::new(matcher));
+        self
+    }
+
+    /// Blocks until the method has been called `count` times.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the method was not called `count` times before the
+    /// timeout elapsed, or if the method was never stubbed.
+    pub fn wait(self, count: usize, timeout: Duration) {
+        let mock = mock(self.faux, self.id, self.method);
+        let calls = mock.watch(self.matcher);
+        if !mock.wait_for(&calls, count, timeout) {
+            panic!(
+                "faux: timed out after {:?} waiting for `{}::{}` to be called {} times; calls so far: {}",
+                timeout,
+                self.faux.store.struct_name,
+                self.method.name(),
+                count,
+                calls.load(Ordering::SeqCst),
+            );
+        }
+    }
+
+    /// Resolves once the method has been called `count` times.
+    ///
+    /// It never times out on its own. Use the timer of your runtime
+    /// to bound the wait.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the method was never stubbed.
+    pub fn wait_async(self, count: usize) -> WaitCalls<'m, R, I, O> {
+        let calls = mock(self.faux, self.id, self.method).watch(self.matcher);
+        WaitCalls {
+            id: self.id,
+            method: self.method,
+            faux: self.faux,
+            calls,
+            target: count,
+        }
+    }
+}
+
+/// Future returned by [`Calls::wait_async`].
+pub struct WaitCalls<'m, R, I, O> {
+    id: fn(R, I) -> O,
+    method: MethodId,
+    faux: &'m Faux,
+    calls: Arc<AtomicUsize>,
+    target: usize,
+}
+
+impl<R, I, O> Future for WaitCalls<'_, R, I, O> {
+    type Output = ();
+
+    fn poll(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<()> {
+        mock(self.faux, self.id, self.method).poll_for(&self.calls, self.target, cx.waker())
+    }
+}
+
+fn mock<R, I, O>(faux: &Faux, id: fn(R, I) -> O, method: MethodId) -> &Mock<'static, I, O> {
+    // Safety: the id was generated by #[faux::methods] alongside the
+    // types of the method it identifies
+    match unsafe { faux.store.get(id, method, "") } {
+        Ok(mock) => mock,
+        Err(_) => panic!(
+            "faux: cannot wait for calls to `{}::{}` because it was never stubbed",
+            faux.store.struct_name,
+            method.name()
+        ),
+    }
+}
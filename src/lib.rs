@@ -1,4 +1,5 @@
 #![allow(clippy::needless_doctest_main)]
+#![allow(clippy::test_attr_in_doctest)]
 
 //! A library to create [mocks] out of structs.
 //!
@@ -250,14 +251,23 @@
 //!
 //! [mocks]: https://martinfowler.com/articles/mocksArentStubs.html
 
+mod associated;
+mod calls;
 pub mod matcher;
+mod mock_fn;
+mod mockable;
+mod scope;
+pub mod sync;
 pub mod when;
 
+#[doc(hidden)]
+pub mod arg_debug;
+
 /// Transforms a struct into a mockable version of itself.
 ///
 /// An associated function called `faux` is created for the tagged
 /// struct, masking the original definition of the struct by changing
-/// its name.
+/// its name. The struct also implements [`Mockable`].
 ///
 /// Use [`cargo-expand`] to see the changes to your struct after macro
 /// expansion.
@@ -294,6 +304,137 @@ pub mod when;
 /// # }
 /// ```
 ///
+/// # Enums
+///
+/// Enums are wrapped the same way as structs, so the mockable version
+/// is a struct that no longer has any variants. Only methods within
+/// `impl` blocks tagged by [`#[methods]`](methods) may construct or
+/// match on the enum's variants.
+///
+/// ```
+/// #[faux::create]
+/// pub enum Backend {
+///     Local(String),
+///     Remote { url: String },
+///     Memory,
+/// }
+///
+/// #[faux::methods]
+/// impl Backend {
+///     pub fn local(path: &str) -> Self {
+///         Backend::Local(path.to_owned())
+///     }
+///
+///     pub fn location(&self) -> String {
+///         match self {
+///             Backend::Local(path) => path.clone(),
+///             Backend::Remote { url } => url.clone(),
+///             Backend::Memory => String::from("memory"),
+///         }
+///     }
+/// }
+///
+/// # fn main() {
+/// assert_eq!(Backend::local("/tmp").location(), "/tmp");
+///
+/// let mut backend = Backend::faux();
+/// faux::when!(backend.location()).then_return(String::from("mocked"));
+/// assert_eq!(backend.location(), "mocked");
+/// # }
+/// ```
+///
+/// Using the variants anywhere else fails to compile. Unit variants,
+/// and tuple variants used as functions, fail with an error pointing
+/// at the variant:
+///
+/// ```text
+/// error[E0080]: evaluation panicked: faux: `Backend` is a mockable enum so its variants may only be used within #[faux::methods]
+/// ```
+///
+/// Outside of patterns, the error is only raised once the code using
+/// the variant is built, so `cargo check` does not report it.
+///
+/// ```compile_fail,E0080
+/// # #[faux::create]
+/// # pub enum Backend {
+/// #     Local(String),
+/// #     Remote { url: String },
+/// #     Memory,
+/// # }
+/// # fn main() {
+/// # let backend = Backend::faux();
+/// let memory = Backend::Memory;
+/// # }
+/// ```
+///
+/// ```compile_fail,E0080
+/// # #[faux::create]
+/// # pub enum Backend {
+/// #     Local(String),
+/// #     Remote { url: String },
+/// #     Memory,
+/// # }
+/// # fn main() {
+/// # let backend = Backend::faux();
+/// let is_memory = matches!(backend, Backend::Memory);
+/// # }
+/// ```
+///
+/// ```compile_fail,E0080
+/// # #[faux::create]
+/// # pub enum Backend {
+/// #     Local(String),
+/// #     Remote { url: String },
+/// #     Memory,
+/// # }
+/// # fn main() {
+/// # let backend = Backend::faux();
+/// let local = Backend::Local(String::from("/tmp"));
+/// # }
+/// ```
+///
+/// Tuple variants in patterns, and struct variants, fail with the
+/// compiler's own errors instead, which do not explain why:
+///
+/// ```compile_fail,E0164
+/// # #[faux::create]
+/// # pub enum Backend {
+/// #     Local(String),
+/// #     Remote { url: String },
+/// #     Memory,
+/// # }
+/// # fn main() {
+/// # let backend = Backend::faux();
+/// let is_local = matches!(backend, Backend::Local(_));
+/// # }
+/// ```
+///
+/// ```compile_fail,E0223
+/// # #[faux::create]
+/// # pub enum Backend {
+/// #     Local(String),
+/// #     Remote { url: String },
+/// #     Memory,
+/// # }
+/// # fn main() {
+/// # let backend = Backend::faux();
+/// let remote = Backend::Remote { url: String::from("localhost") };
+/// # }
+/// ```
+///
+/// ```compile_fail,E0223
+/// # #[faux::create]
+/// # pub enum Backend {
+/// #     Local(String),
+/// #     Remote { url: String },
+/// #     Memory,
+/// # }
+/// # fn main() {
+/// # let backend = Backend::faux();
+/// let is_remote = matches!(backend, Backend::Remote { .. });
+/// # }
+/// ```
+///
 /// # Attribute arguments
 ///
 /// ## self_type
@@ -364,7 +505,35 @@ pub mod when;
 /// * `#[create(self_type = "Arc")]`
 /// * `#[create(self_type = "Box")]`
 ///
+/// ## cfg
+///
+/// Only makes the struct mockable when the given [cfg predicate] is
+/// true. Otherwise, the struct is left untouched so there is no
+/// overhead from `faux` in those builds.
+///
+/// This replaces pairs of `#[cfg_attr(test, faux::create)]` and
+/// `#[cfg_attr(test, faux::methods)]`. It must be set to the same
+/// value in all [`#[methods]`](methods) for this struct. Since the
+/// attribute is always expanded, `faux` cannot be an optional
+/// dependency when using `cfg`.
+///
+/// ### Examples
+///
+/// ```
+/// #[faux::create(cfg = "any(test, feature = \"mocks\")")]
+/// pub struct MyStruct {
+///     /* private fields */
+/// }
+///
+/// #[faux::methods(cfg = "any(test, feature = \"mocks\")")]
+/// impl MyStruct {
+///     /* methods go here */
+/// }
+/// # fn main() {}
+/// ```
+///
 /// [`cargo-expand`]: https://github.com/dtolnay/cargo-expand
+/// [cfg predicate]: https://doc.rust-lang.org/reference/conditional-compilation.html
 ///
 pub use faux_macros::create;
 
@@ -373,8 +542,14 @@ pub use faux_macros::create;
 ///
 /// Mockable methods can be mocked using [`when!`].
 ///
-/// Associated functions and private methods cannot be mocked. Calls
-/// to them are proxied to the real implementation.
+/// Private methods cannot be mocked unless opted into using the
+/// [`private`](#private) argument. Calls to them are proxied to the
+/// real implementation.
+///
+/// Public associated functions, such as constructors, are proxied to
+/// the real implementation unless they were stubbed on the current
+/// thread. See [stubbing associated
+/// functions](when!#associated-functions).
 ///
 /// # Requirements
 ///
@@ -419,7 +594,7 @@ pub use faux_macros::create;
 /// }
 ///
 /// # fn main() {
-/// // #[methods] will not mock associated functions
+/// // associated functions that are not stubbed are not mocked
 /// // thus allowing you to still create real instances
 /// let real = MyStruct::new(vec![5]);
 /// assert_eq!(real.get(), 20);
@@ -546,6 +721,50 @@ pub use faux_macros::create;
 /// mockable. `self_type` does not specify what types of receivers can
 /// be mocked, but how `faux` stores the instances internally.
 ///
+/// ## private
+///
+/// Makes private methods and associated functions mockable. They may
+/// only be stubbed from within the module that defines them, such as
+/// its unit tests. Otherwise, calling a private method of a mock
+/// instance panics.
+///
+/// ### Examples
+///
+/// ```
+/// #[faux::create]
+/// pub struct Parser {}
+///
+/// #[faux::methods(private = "stub")]
+/// impl Parser {
+///     pub fn parse(&self, input: &str) -> Option<u32> {
+///         self.validate(input).then_some(input.len() as u32)
+///     }
+///
+///     fn validate(&self, input: &str) -> bool {
+///         /* implementation code */
+///         # panic!()
+///     }
+/// }
+///
+/// # fn main() {
+/// let mut parser = Parser::faux();
+/// faux::when!(parser.validate).then_return(false);
+/// assert!(!parser.validate("input"));
+/// # }
+/// ```
+///
+/// ### Allowed values:
+/// * `#[methods(private = "panic")]` (default)
+/// * `#[methods(private = "stub")]`
+///
+/// ## cfg
+///
+/// Only makes the methods mockable when the given cfg predicate is
+/// true. Otherwise, the `impl` block is left untouched.
+///
+/// It must be set to the same value as in [`#[create]`](create#cfg)
+/// for this struct.
+///
 /// # Panics
 ///
 /// ## Non-stubbed methods
@@ -706,6 +925,194 @@ pub use faux_macros::create;
 /// [receiver]: https://doc.rust-lang.org/reference/items/associated-items.html#methods
 pub use faux_macros::methods;
 
+/// Creates a mock of a trait.
+///
+/// Generates a struct named `Mock{Trait}` in the same module as the
+/// trait, with the trait's visibility, that implements the trait.
+/// The struct is created as if by [`#[create]`](create) and its
+/// implementation of the trait as if by [`#[methods]`](methods), so
+/// instances are created using `faux()` and every method of the trait
+/// is stubbed using [`when!`].
+///
+/// Methods with default implementations are mocked as well and must
+/// be stubbed before being called.
+///
+/// # Examples
+///
+/// ```
+/// #[cfg_attr(test, faux::trait_mock)]
+/// # #[faux::trait_mock]
+/// pub trait Storage {
+///     fn get(&self, key: &str) -> Option<String>;
+///
+///     fn set(&mut self, key: String, value: String);
+/// }
+///
+/// fn greeting(storage: &dyn Storage) -> String {
+///     format!("hello {}", storage.get("name").unwrap_or_default())
+/// }
+///
+/// # fn main() {
+/// let mut storage = MockStorage::faux();
+/// faux::when!(storage.get("name")).then_return(Some("faux".to_string()));
+///
+/// assert_eq!(greeting(&storage), "hello faux");
+/// # }
+/// ```
+///
+/// # Attribute arguments
+///
+/// ## name
+///
+/// Overrides the name of the generated struct.
+///
+/// ```
+/// #[faux::trait_mock(name = "FakeClock")]
+/// pub trait Clock {
+///     fn now(&self) -> u64;
+/// }
+///
+/// # fn main() {
+/// let mut clock = FakeClock::faux();
+/// faux::when!(clock.now).then_return(5);
+/// assert_eq!(clock.now(), 5);
+/// # }
+/// ```
+///
+/// # Known Limitations
+///
+/// Generic traits, associated types, associated constants, and
+/// associated functions without a receiver are not supported.
+pub use faux_macros::trait_mock;
+
+/// Creates a mockable wrapper around a type from another crate.
+///
+/// Types defined in other crates cannot be tagged with
+/// [`#[create]`](create) and [`#[methods]`](methods).
+/// `mock_extern!` instead takes a struct wrapping the foreign type
+/// and the signatures of the methods to mock, and generates them as
+/// if they were tagged. Real instances delegate every method to the
+/// foreign type, while mock instances are created using `faux()` and
+/// stubbed using [`when!`].
+///
+/// Real instances are created either by an associated function
+/// returning `Self`, `Result<Self, E>`, `Option<Self>` or `Box<Self>`,
+/// which delegates to the foreign function of the same name, or by
+/// converting from the foreign type using [`From`].
+///
+/// To only mock the foreign type in tests, place the macro behind
+/// `#[cfg(test)]` next to a `#[cfg(not(test))]` import of the foreign
+/// type.
+///
+/// Methods without a visibility are made public so they can be
+/// mocked. Methods of trait implementations delegate to the foreign
+/// type's implementation of the trait.
+///
+/// # Examples
+///
+/// ```
+/// mod http {
+///     // a type from another crate
+///     pub struct Client { /* fields */ }
+///
+///     impl Client {
+///         pub fn new() -> Self {
+///             /* implementation code */
+///             # Client {}
+///         }
+///
+///         pub fn get(&self, url: &str) -> Result<String, String> {
+///             /* implementation code */
+///             # Ok(url.to_string())
+///         }
+///     }
+/// }
+///
+/// faux::mock_extern! {
+///     pub struct Client(http::Client);
+///
+///     impl Client {
+///         fn new() -> Self;
+///         fn get(&self, url: &str) -> Result<String, String>;
+///     }
+/// }
+///
+/// fn fetch(client: &Client) -> String {
+///     client.get("/faux").unwrap_or_default()
+/// }
+///
+/// # fn main() {
+/// let real = Client::new();
+/// assert_eq!(fetch(&real), "/faux");
+///
+/// let mut fake = Client::faux();
+/// faux::when!(fake.get).then_return(Err("not found".to_string()));
+/// assert_eq!(fetch(&fake), "");
+/// # }
+/// ```
+///
+/// # Known Limitations
+///
+/// Generic structs and receivers other than `self`, `&self`, and
+/// `&mut self` are not supported. Every argument must be named.
+pub use faux_macros::mock_extern;
+
+/// Makes a free function mockable.
+///
+/// The function keeps running its real body unless it was stubbed on
+/// the current thread using [`when!`], with or without argument
+/// matchers. Stubs are kept per thread so tests running in parallel
+/// do not affect each other. Remove them using [`clear_stubs`] or a
+/// [`StubsGuard`].
+///
+/// Once stubbed, calls that match none of the function's stubs panic
+/// as they would for a method.
+///
+/// # Examples
+///
+/// ```
+/// mod fs_helpers {
+///     #[faux::function]
+///     pub fn read_config(path: &str) -> Result<String, String> {
+///         /* implementation code */
+///         # Ok(path.to_string())
+///     }
+/// }
+///
+/// fn port(path: &str) -> u16 {
+///     fs_helpers::read_config(path)
+///         .map_or(80, |config| config.parse().unwrap_or(80))
+/// }
+///
+/// # fn main() {
+/// faux::when!(fs_helpers::read_config("dev.toml")).then(|_| Ok("8080".to_string()));
+///
+/// assert_eq!(port("dev.toml"), 8080);
+/// # }
+/// ```
+///
+/// # Attribute arguments
+///
+/// ## cfg
+///
+/// Every call to a mockable function first looks for stubs on the
+/// current thread. To keep that lookup out of other builds, only make
+/// the function mockable when the given [cfg predicate] is true, e.g.
+/// `#[faux::function(cfg = "test")]`. Otherwise, the function is left
+/// untouched. `#[cfg_attr(test, faux::function)]` works as well.
+///
+/// ```
+/// #[faux::function(cfg = "any(test, feature = \"mocks\")")]
+/// pub fn read_config(path: &str) -> Result<String, String> {
+///     /* implementation code */
+///     # Ok(path.to_string())
+/// }
+/// # fn main() {}
+/// ```
+///
+/// [cfg predicate]: https://doc.rust-lang.org/reference/conditional-compilation.html
+pub use faux_macros::function;
+
 /// Creates a [`When`] instance to stub a specific method in a struct.
 ///
 /// Callers may specify argument matchers to limit the arguments for
@@ -759,8 +1166,10 @@ pub use faux_macros::methods;
 /// An argument mismatch would look something like:
 ///
 /// ```term
-/// thread 'main' panicked at 'failed to call stub on 'Foo::some_method':
-/// ✗ Arguments did not match
+/// thread 'main' panicked at src/main.rs:18:10:
+/// `Foo::some_method` had no suitable stubs. Closest stub: arguments 0 and 1 differed.
+/// Existing stubs failed because:
+/// ✗ src/main.rs:15:5: Arguments did not match
 ///   Expected: [8, 9]
 ///   Actual:   [1, 1]
 ///
@@ -772,6 +1181,35 @@ pub use faux_macros::methods;
 ///     Actual:   1
 /// ```
 ///
+/// If an argument was matched for equality and either its expected or
+/// actual value spans multiple lines when pretty-printed (`{:#?}`),
+/// a line-by-line diff of the two follows:
+///
+/// ```term
+///   Argument 0:
+///     Expected: Data { a: 2, b: 3 }
+///     Actual:   Data { a: 4, b: 3 }
+///     Diff (- expected, + actual):
+///       Data {
+///     -     a: 2,
+///     +     a: 4,
+///           b: 3,
+///       }
+/// ```
+///
+/// Set the `FAUX_COLOR` environment variable to `always` to color
+/// the diff. Coloring is disabled if `NO_COLOR` is set.
+///
+/// Each stub is listed with the location of the `when!` that
+/// created it.
+///
+/// When a method has multiple stubs, the one that came closest to
+/// matching is listed first and called out in the first line of the
+/// message, e.g. `Closest stub: argument 1 differed.`. The message
+/// ends with the arguments of the last few invocations of the
+/// method. Arguments that do not implement `Debug` are shown as
+/// `<?>`.
+///
 /// # Argument Matchers
 ///
 /// Argument matchers are specified by passing them to `when!`:
@@ -885,6 +1323,61 @@ pub use faux_macros::methods;
 ///
 /// ```
 ///
+/// # Associated functions
+///
+/// Associated functions, such as constructors, are stubbed by passing
+/// their path to `when!`, with or without argument matchers. Their
+/// stubs are kept by the current thread rather than by a mock
+/// instance, so code calling the function on the same thread
+/// receives the stubbed output.
+///
+/// Associated functions that were never stubbed on the current thread
+/// call their real implementation. Once stubbed, calls that match
+/// none of their stubs panic as they would for a method.
+///
+/// ```
+/// #[faux::create]
+/// pub struct Connection {
+///     /* fields */
+/// }
+///
+/// #[faux::methods]
+/// impl Connection {
+///     pub fn connect(addr: &str) -> Result<Self, String> {
+///         /* implementation code */
+///         # panic!()
+///     }
+///
+///     pub fn ping(&self) -> bool {
+///         /* implementation code */
+///         # panic!()
+///     }
+/// }
+///
+/// fn is_up(addr: &str) -> bool {
+///     Connection::connect(addr).map_or(false, |c| c.ping())
+/// }
+///
+/// # fn main() {
+/// let mut connection = Connection::faux();
+/// faux::when!(connection.ping).then_return(true);
+///
+/// faux::when!(Connection::connect(_)).then(|_| Err("down".to_string()));
+/// faux::when!(Connection::connect("localhost"))
+///     .once()
+///     .then_return(Ok(connection));
+///
+/// assert!(is_up("localhost"));
+/// assert!(!is_up("remote"));
+/// # }
+/// ```
+///
+/// The stubs cannot be held onto past the statement that created
+/// them, e.g. by binding the result of `when!` to a variable.
+///
+/// Whether they were called as many times as expected is only checked
+/// once they are removed using [`clear_stubs`] or a [`StubsGuard`].
+///
 /// [`When`]: struct.When.html
 /// [`any()`]: matcher/fn.any.html
 /// [`eq_against({expr})`]: matcher/fn.eq_against.html
@@ -898,6 +1391,80 @@ pub use faux_macros::when;
 #[doc(inline)]
 pub use when::When;
 
+/// Waits for a method of a mock to be called a number of times.
+///
+/// Use it when the code under test calls the mock from another
+/// thread or task. With a timeout, it blocks until the method was
+/// called `count` times and panics if the timeout elapses first:
+///
+/// ```ignore
+/// faux::await_calls!(mock.method(args...), count, timeout);
+/// ```
+///
+/// Without one, it returns a future that resolves once the method was
+/// called `count` times:
+///
+/// ```ignore
+/// faux::await_calls!(mock.method(args...), count).await;
+/// ```
+///
+/// The method is passed as in [`when!`], so the arguments are
+/// argument matchers. When every argument is `_` or the arguments are
+/// left out, all calls are counted, including the ones made before
+/// waiting. Otherwise, the arguments are matched as calls are made,
+/// so only the calls made after the wait starts that match them are
+/// counted.
+///
+/// Panics if the method was never stubbed, since calling it would
+/// panic anyway.
+///
+/// # Examples
+///
+/// ```
+/// use std::{sync::Arc, thread, time::Duration};
+///
+/// #[faux::create]
+/// pub struct Store {}
+///
+/// #[faux::methods]
+/// impl Store {
+///     pub fn flush(&self, key: u32) {
+///       /* implementation code */
+///       # panic!()
+///     }
+/// }
+///
+/// # fn main() {
+/// let mut store = Store::faux();
+/// faux::when!(store.flush).then_return(());
+///
+/// let store = Arc::new(store);
+/// {
+///     let store = store.clone();
+///     thread::spawn(move || (0..3).for_each(|key| store.flush(key)));
+/// }
+///
+/// faux::await_calls!(store.flush(_), 3, Duration::from_secs(1));
+/// # }
+/// ```
+///
+/// [`when!`]: crate::when!
+pub use faux_macros::await_calls;
+
+#[doc(inline)]
+pub use calls::{Calls, WaitCalls};
+
+#[doc(hidden)]
+pub use associated::Associated;
+pub use associated::{clear_stubs, StubsGuard};
+#[doc(hidden)]
+pub use mock::MethodId;
+pub use mock_fn::{mock_fn, FnArgs, MockFn};
+#[doc(hidden)]
+pub use mockable::sealed;
+pub use mockable::{MockStore, Mockable};
+pub use scope::{scope, Scope};
+
 #[doc(inline)]
 pub use matcher::ArgMatcher;
 
@@ -953,6 +1520,22 @@ impl<T> MaybeFaux<T> {
     pub fn faux(name: &'static str) -> Self {
         MaybeFaux::Faux(Faux::new(name))
     }
+
+    /// The mock, `None` for a real instance
+    pub fn faux_ref(&self) -> Option<&Faux> {
+        match self {
+            MaybeFaux::Real(_) => None,
+            MaybeFaux::Faux(faux) => Some(faux),
+        }
+    }
+
+    /// The mock, `None` for a real instance
+    pub fn faux_mut(&mut self) -> Option<&mut Faux> {
+        match self {
+            MaybeFaux::Real(_) => None,
+            MaybeFaux::Faux(faux) => Some(faux),
+        }
+    }
 }
 
 /// The internal representation of a mock object
@@ -961,11 +1544,25 @@ impl<T> MaybeFaux<T> {
 /// documented. Its mere existence is an implementation detail and not
 /// meant to be relied upon.
 #[doc(hidden)]
-#[derive(Clone, Debug)]
+#[derive(Debug)]
 pub struct Faux {
     store: Arc<mock::Store<'static>>,
 }
 
+impl Clone for Faux {
+    fn clone(&self) -> Self {
+        // a clone could keep using scoped stubs after their scope
+        assert!(
+            !self.store.is_scoped(),
+            "faux: `{}` may not be cloned while a scope is open on it",
+            self.store.struct_name
+        );
+        Faux {
+            store: self.store.clone(),
+        }
+    }
+}
+
 impl Faux {
     pub fn new(name: &'static str) -> Self {
         Faux {
@@ -973,6 +1570,14 @@ impl Faux {
         }
     }
 
+    /// Creates the stubs of a thread's registry, see
+    /// [`mock::Store::registered`]
+    pub(crate) fn registered(name: &'static str) -> Self {
+        Faux {
+            store: Arc::new(mock::Store::registered(name)),
+        }
+    }
+
     /// Return a mutable reference to its internal mock store
     ///
     /// Returns `None` if the store is being shared by multiple mock
@@ -997,17 +1602,91 @@ impl Faux {
     pub unsafe fn call_stub<R, I, O>(
         &self,
         id: fn(R, I) -> O,
-        fn_name: &'static str,
+        method: MethodId,
+        input: I,
+        describe: arg_debug::Input<I>,
+        generics: &'static str,
+    ) -> Result<O, InvocationError> {
+        let mock = self.store.get(id, method, generics)?;
+        let invocation = self.start(mock, &input, describe, generics)?;
+        if let Some(gate) = invocation.gate() {
+            gate.pass();
+        }
+        match self.run(mock, invocation, input, generics)? {
+            mock::Output::Ready(output) => Ok(output),
+            mock::Output::Pending(_) => panic!(
+                "faux: `{}::{}` is not async but its stub answers asynchronously",
+                self.store.struct_name,
+                method.name()
+            ),
+        }
+    }
+
+    #[doc(hidden)]
+    /// Analog of [`call_stub`](Faux::call_stub) for `async` methods.
+    ///
+    /// Awaits the stub if it answers asynchronously.
+    ///
+    /// # Safety
+    ///
+    /// Do *NOT* call this function directly.
+    /// This should only be called by the generated code from #[faux::methods]
+    pub async unsafe fn call_stub_async<R, I, O>(
+        &self,
+        id: fn(R, I) -> O,
+        method: MethodId,
         input: I,
+        describe: arg_debug::Input<I>,
         generics: &'static str,
     ) -> Result<O, InvocationError> {
-        let mock = self.store.get(id, fn_name, generics)?;
-        mock.call(input).map_err(|stub_error| InvocationError {
+        let mock = self.store.get(id, method, generics)?;
+        let invocation = self.start(mock, &input, describe, generics)?;
+        if let Some(gate) = invocation.gate() {
+            gate.pass_async().await;
+        }
+        match self.run(mock, invocation, input, generics)? {
+            mock::Output::Ready(output) => Ok(output),
+            mock::Output::Pending(output) => Ok(output.await),
+        }
+    }
+
+    /// Claims the stub that answers `input`, whose gate the call
+    /// passes before running it
+    fn start<'m, I, O>(
+        &self,
+        mock: &'m mock::Mock<'static, I, O>,
+        input: &I,
+        describe: arg_debug::Input<I>,
+        generics: &'static str,
+    ) -> Result<mock::Invocation<'m, 'static, I, O>, InvocationError> {
+        mock.start(input, describe)
+            .map_err(|stub_error| self.error(mock, generics, stub_error))
+    }
+
+    fn run<I, O>(
+        &self,
+        mock: &mock::Mock<'static, I, O>,
+        invocation: mock::Invocation<'_, 'static, I, O>,
+        input: I,
+        generics: &'static str,
+    ) -> Result<mock::Output<'static, O>, InvocationError> {
+        invocation
+            .run(input)
+            .map_err(|stub_error| self.error(mock, generics, stub_error))
+    }
+
+    fn error<I, O>(
+        &self,
+        mock: &mock::Mock<'static, I, O>,
+        generics: &'static str,
+        stub_error: mock::InvocationError,
+    ) -> InvocationError {
+        InvocationError {
             fn_name: mock.name(),
             struct_name: self.store.struct_name,
             generics,
             stub_error,
-        })
+        }
     }
 }
 
@@ -1033,12 +1712,35 @@ impl fmt::Display for InvocationError {
                     self.struct_name, self.fn_name, generics
                 )
             }
-            mock::InvocationError::Stub(errors) => {
-                writeln!(
+            mock::InvocationError::Recursive { location } => {
+                write!(
                     f,
-                    "`{}::{}{}` had no suitable stubs. Existing stubs failed because:",
+                    "`{}::{}{}` was called by its own stub while that stub was answering on the same thread. The stub was created at {}. To answer the inner call, stub it with more specific arguments after this stub.",
+                    self.struct_name, self.fn_name, generics, location
+                )
+            }
+            mock::InvocationError::Stub { errors, history } => {
+                write!(
+                    f,
+                    "`{}::{}{}` had no suitable stubs.",
                     self.struct_name, self.fn_name, generics
                 )?;
+                if let Some(closest) = errors.first().and_then(|e| e.mismatched_args()) {
+                    f.write_str(" Closest stub: ")?;
+                    match closest {
+                        [] if matches!(errors[0].reason, mock::stub::Reason::Never) => f
+                            .write_str(
+                                "all arguments matched but it expected to never be called.",
+                            )?,
+                        [] => f.write_str("all arguments matched but it was exhausted.")?,
+                        [arg] => write!(f, "argument {} differed.", arg)?,
+                        [args @ .., last] => {
+                            let args: Vec<_> = args.iter().map(|a| a.to_string()).collect();
+                            write!(f, "arguments {} and {} differed.", args.join(", "), last)?
+                        }
+                    }
+                }
+                f.write_str("\nExisting stubs failed because:\n")?;
                 let mut errors = errors.iter();
                 if let Some(e) = errors.next() {
                     f.write_str("✗ ")?;
@@ -1047,6 +1749,49 @@ impl fmt::Display for InvocationError {
                 errors.try_for_each(|e| {
                     f.write_str("\n\n✗ ")?;
                     fmt::Display::fmt(e, f)
+                })?;
+
+                if history.total == 0 {
+                    return write!(
+                        f,
+                        "\n\n`{}::{}{}` had not been called before.",
+                        self.struct_name, self.fn_name, generics
+                    );
+                }
+                if let Some(panicked) = &history.panicked {
+                    write!(
+                        f,
+                        "\n\nA previous invocation of `{}::{}{}` panicked: {}",
+                        self.struct_name, self.fn_name, generics, panicked
+                    )?;
+                }
+                if history.recent.is_empty() {
+                    return write!(
+                        f,
+                        "\n\n`{}::{}{}` was called {} time{} before. Its arguments were not kept to list them.",
+                        self.struct_name,
+                        self.fn_name,
+                        generics,
+                        history.total,
+                        if history.total == 1 { "" } else { "s" }
+                    );
+                }
+                if history.total > history.recent.len() {
+                    write!(
+                        f,
+                        "\n\nPrevious calls (last {} of {}, most recent last):",
+                        history.recent.len(),
+                        history.total
+                    )?;
+                } else {
+                    f.write_str("\n\nPrevious calls (most recent last):")?;
+                }
+                history.recent.iter().try_for_each(|args| {
+                    write!(
+                        f,
+                        "\n  {}::{}{}({})",
+                        self.struct_name, self.fn_name, generics, args
+                    )
                 })
             }
         }
@@ -8,6 +8,7 @@
 //! the macro.
 
 mod any;
+mod diff;
 mod eq;
 mod from_fn;
 mod invocation_matcher;
@@ -74,6 +75,23 @@ pub trait ArgMatcher<Arg: ?Sized>: fmt::Display {
     /// ```
     fn matches(&self, argument: &Arg) -> bool;
 
+    /// Returns the pretty-printed (`{:#?}`) value this matcher
+    /// expects the argument to equal, if it is an equality matcher.
+    ///
+    /// When provided, a mismatched argument is displayed alongside a
+    /// line-by-line diff of its own pretty-printed value against this
+    /// one. Defaults to `None`.
+    ///
+    /// ```
+    /// use faux::matcher::{self, ArgMatcher};
+    ///
+    /// assert_eq!(ArgMatcher::<i32>::pretty_expected(&matcher::eq(5)), Some("5".to_string()));
+    /// assert_eq!(ArgMatcher::<i32>::pretty_expected(&matcher::any()), None);
+    /// ```
+    fn pretty_expected(&self) -> Option<String> {
+        None
+    }
+
     /// Converts the `Argmatcher<Arg>` into an `ArgMatcher<&Arg>` to
     /// test against the reference of the argument.
     fn into_ref_matcher(self) -> RefMatcher<Self>
@@ -92,6 +110,10 @@ impl<Arg, AM: ArgMatcher<Arg>> ArgMatcher<&Arg> for RefMatcher<AM> {
     fn matches(&self, actual: &&Arg) -> bool {
         self.0.matches(*actual)
     }
+
+    fn pretty_expected(&self) -> Option<String> {
+        self.0.pretty_expected()
+    }
 }
 
 impl<AM: fmt::Display> fmt::Display for RefMatcher<AM> {
@@ -0,0 +1,231 @@
+//! Line-by-line diffs of pretty-printed arguments.
+//!
+//! Used to explain why an equality matcher failed when the expected
+//! and actual values are too large to compare by eye.
+
+use std::{ffi::OsStr, fmt::Write};
+
+const RED: &str = "\x1b[31m";
+const GREEN: &str = "\x1b[32m";
+const RESET: &str = "\x1b[0m";
+
+/// The largest number of cells in the table of common subsequence
+/// lengths. Larger inputs are diffed as a block of removed lines
+/// followed by a block of added lines.
+const MAX_TABLE_SIZE: usize = 1 << 16;
+
+/// Returns a diff of the lines of `expected` against `actual`.
+///
+/// Lines only in `expected` are prefixed by `-`, lines only in
+/// `actual` by `+`, and shared lines by a space. Each line is
+/// prefixed by `indent`.
+pub fn lines(expected: &str, actual: &str, indent: &str) -> String {
+    let expected: Vec<_> = expected.lines().collect();
+    let actual: Vec<_> = actual.lines().collect();
+    let color = use_color();
+
+    let mut diff = String::new();
+    for line in lcs_diff(&expected, &actual) {
+        let (sign, text, paint) = match line {
+            Line::Same(text) => (' ', text, None),
+            Line::Removed(text) => ('-', text, Some(RED)),
+            Line::Added(text) => ('+', text, Some(GREEN)),
+        };
+        match paint.filter(|_| color) {
+            Some(paint) => writeln!(diff, "{indent}{paint}{sign} {text}{RESET}"),
+            None => writeln!(diff, "{indent}{sign} {text}"),
+        }
+        .expect("writing to a String cannot fail");
+    }
+
+    diff.truncate(diff.trim_end().len());
+    diff
+}
+
+/// Whether to color the diff using ANSI escape codes.
+///
+/// Coloring is opt-in by setting `FAUX_COLOR` to `always`. It is
+/// never used if `NO_COLOR` is set to a non-empty value.
+fn use_color() -> bool {
+    color_enabled(
+        std::env::var_os("NO_COLOR").as_deref(),
+        std::env::var_os("FAUX_COLOR").as_deref(),
+    )
+}
+
+fn color_enabled(no_color: Option<&OsStr>, faux_color: Option<&OsStr>) -> bool {
+    let no_color = no_color.map_or(false, |v| !v.is_empty());
+    !no_color && faux_color.map_or(false, |v| v == "always")
+}
+
+#[derive(Debug, PartialEq, Eq)]
+enum Line<'a> {
+    Same(&'a str),
+    Removed(&'a str),
+    Added(&'a str),
+}
+
+/// Diffs two sequences of lines using their longest common subsequence
+fn lcs_diff<'a>(expected: &[&'a str], actual: &[&'a str]) -> Vec<Line<'a>> {
+    // the shared head and tail need no table
+    let prefix = expected
+        .iter()
+        .zip(actual)
+        .take_while(|(e, a)| e == a)
+        .count();
+    let suffix = expected[prefix..]
+        .iter()
+        .rev()
+        .zip(actual[prefix..].iter().rev())
+        .take_while(|(e, a)| e == a)
+        .count();
+
+    let mut diff = Vec::with_capacity(expected.len().max(actual.len()));
+    diff.extend(expected[..prefix].iter().map(|l| Line::Same(l)));
+    diff_middle(
+        &expected[prefix..expected.len() - suffix],
+        &actual[prefix..actual.len() - suffix],
+        &mut diff,
+    );
+    diff.extend(
+        expected[expected.len() - suffix..]
+            .iter()
+            .map(|l| Line::Same(l)),
+    );
+    diff
+}
+
+/// Diffs two sequences that differ in their first and last lines
+fn diff_middle<'a>(expected: &[&'a str], actual: &[&'a str], diff: &mut Vec<Line<'a>>) {
+    let too_large = (expected.len() + 1)
+        .checked_mul(actual.len() + 1)
+        .map_or(true, |cells| cells > MAX_TABLE_SIZE);
+    if too_large {
+        diff.extend(expected.iter().map(|l| Line::Removed(l)));
+        diff.extend(actual.iter().map(|l| Line::Added(l)));
+        return;
+    }
+
+    // lengths[i * width + j] is the length of the LCS of expected[i..] and actual[j..]
+    let width = actual.len() + 1;
+    let mut lengths = vec![0_usize; (expected.len() + 1) * width];
+    for i in (0..expected.len()).rev() {
+        for j in (0..actual.len()).rev() {
+            lengths[i * width + j] = if expected[i] == actual[j] {
+                lengths[(i + 1) * width + j + 1] + 1
+            } else {
+                lengths[(i + 1) * width + j].max(lengths[i * width + j + 1])
+            };
+        }
+    }
+
+    let (mut i, mut j) = (0, 0);
+    while i < expected.len() && j < actual.len() {
+        if expected[i] == actual[j] {
+            diff.push(Line::Same(expected[i]));
+            i += 1;
+            j += 1;
+        } else if lengths[(i + 1) * width + j] >= lengths[i * width + j + 1] {
+            diff.push(Line::Removed(expected[i]));
+            i += 1;
+        } else {
+            diff.push(Line::Added(actual[j]));
+            j += 1;
+        }
+    }
+    diff.extend(expected[i..].iter().map(|l| Line::Removed(l)));
+    diff.extend(actual[j..].iter().map(|l| Line::Added(l)));
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn identical_lines() {
+        assert_eq!(
+            lcs_diff(&["a", "b"], &["a", "b"]),
+            vec![Line::Same("a"), Line::Same("b")]
+        );
+    }
+
+    #[test]
+    fn changed_line() {
+        assert_eq!(
+            lcs_diff(&["{", "a: 2,", "b: 3,", "}"], &["{", "a: 4,", "b: 3,", "}"]),
+            vec![
+                Line::Same("{"),
+                Line::Removed("a: 2,"),
+                Line::Added("a: 4,"),
+                Line::Same("b: 3,"),
+                Line::Same("}"),
+            ]
+        );
+    }
+
+    #[test]
+    fn keeps_common_lines_in_the_middle() {
+        assert_eq!(
+            lcs_diff(&["a", "x", "b"], &["y", "x", "z"]),
+            vec![
+                Line::Removed("a"),
+                Line::Added("y"),
+                Line::Same("x"),
+                Line::Removed("b"),
+                Line::Added("z"),
+            ]
+        );
+    }
+
+    #[test]
+    fn empty_sides() {
+        assert_eq!(lcs_diff(&[], &["a"]), vec![Line::Added("a")]);
+        assert_eq!(lcs_diff(&["a"], &[]), vec![Line::Removed("a")]);
+        assert_eq!(lcs_diff(&[], &[]), vec![]);
+    }
+
+    #[test]
+    fn large_inputs_fall_back_to_blocks() {
+        // every other line is shared but neither the head nor the tail
+        let expected: Vec<_> = (0..1000)
+            .map(|i| if i % 2 == 0 { "e" } else { "x" })
+            .collect();
+        let actual: Vec<_> = (0..1000)
+            .map(|i| if i % 2 == 0 { "x" } else { "a" })
+            .collect();
+        let diff = lcs_diff(&expected, &actual);
+
+        assert_eq!(diff.len(), 2000);
+        assert!(diff[..1000].iter().all(|l| matches!(l, Line::Removed(_))));
+        assert!(diff[1000..].iter().all(|l| matches!(l, Line::Added(_))));
+    }
+
+    #[test]
+    fn large_inputs_keep_shared_head_and_tail() {
+        let mut expected = vec!["same"; 500];
+        let mut actual = expected.clone();
+        expected.insert(250, "old");
+        actual.insert(250, "new");
+
+        let diff = lcs_diff(&expected, &actual);
+        assert_eq!(diff.len(), 502);
+        assert_eq!(diff[250], Line::Removed("old"));
+        assert_eq!(diff[251], Line::Added("new"));
+    }
+
+    #[test]
+    fn color_is_opt_in() {
+        let always = Some(OsStr::new("always"));
+        assert!(!color_enabled(None, None));
+        assert!(!color_enabled(None, Some(OsStr::new("never"))));
+        assert!(color_enabled(None, always));
+    }
+
+    #[test]
+    fn no_color_wins() {
+        let always = Some(OsStr::new("always"));
+        assert!(!color_enabled(Some(OsStr::new("1")), always));
+        // an empty NO_COLOR is ignored
+        assert!(color_enabled(Some(OsStr::new("")), always));
+    }
+}
@@ -15,6 +15,10 @@ where
     fn matches(&self, actual: &Arg) -> bool {
         &self.0 == actual.borrow()
     }
+
+    fn pretty_expected(&self) -> Option<String> {
+        Some(format!("{:#?}", self.0))
+    }
 }
 
 impl<Expected> fmt::Display for Eq<Expected>
@@ -140,6 +144,10 @@ where
     fn matches(&self, actual: &Arg) -> bool {
         &self.0 == actual
     }
+
+    fn pretty_expected(&self) -> Option<String> {
+        Some(format!("{:#?}", self.0))
+    }
 }
 
 impl<Expected> fmt::Display for EqAgainst<Expected>
@@ -1,7 +1,7 @@
 use core::fmt;
 use paste::paste;
 
-use super::ArgMatcher;
+use super::{diff, ArgMatcher};
 
 /// Matcher for the invocation of a method.
 ///
@@ -38,6 +38,34 @@ pub trait InvocationMatcher<Args> {
     /// Returns `Err(String)` if any argument fails to match. The
     /// error should detail which arguments failed and why.
     fn matches(&self, args: &Args) -> Result<(), String>;
+
+    /// Returns whether all arguments match.
+    ///
+    /// This is what `faux` calls when looking for a stub to invoke,
+    /// so it should be cheap. [`matches`](Self::matches) is only
+    /// called to build the error message once no stub was found.
+    ///
+    /// Defaults to checking whether [`matches`](Self::matches)
+    /// succeeds. Override it if building the error is expensive.
+    fn is_match(&self, args: &Args) -> bool {
+        self.matches(args).is_ok()
+    }
+
+    /// Returns the zero-based positions of the arguments that fail
+    /// to match.
+    ///
+    /// `faux` uses this to rank stubs by how close they came to
+    /// matching an invocation. Defaults to `None`, meaning unknown.
+    ///
+    /// ```
+    /// use faux::matcher::{self, InvocationMatcher};
+    ///
+    /// let matcher = (matcher::eq(5), matcher::any(), matcher::eq("hi"));
+    /// assert_eq!(matcher.mismatched_args(&(3, 4, "hi")), Some(vec![0]));
+    /// ```
+    fn mismatched_args(&self, _args: &Args) -> Option<Vec<usize>> {
+        None
+    }
 }
 
 #[doc(hidden)]
@@ -48,6 +76,10 @@ impl<Arg> InvocationMatcher<Arg> for AnyInvocation {
     fn matches(&self, _: &Arg) -> Result<(), String> {
         Ok(())
     }
+
+    fn is_match(&self, _: &Arg) -> bool {
+        true
+    }
 }
 
 impl InvocationMatcher<()> for () {
@@ -55,6 +87,10 @@ impl InvocationMatcher<()> for () {
     fn matches(&self, _: &()) -> Result<(), String> {
         Ok(())
     }
+
+    fn is_match(&self, _: &()) -> bool {
+        true
+    }
 }
 
 impl<Arg: fmt::Debug, AM: ArgMatcher<Arg>> InvocationMatcher<Arg> for (AM,) {
@@ -63,14 +99,47 @@ impl<Arg: fmt::Debug, AM: ArgMatcher<Arg>> InvocationMatcher<Arg> for (AM,) {
         if self.0.matches(arg) {
             Ok(())
         } else {
-            Err(format!(
+            let mut error = format!(
                 "Argument did not match.
 Expected: {}
 Actual:   {:?}",
                 self.0, arg
-            ))
+            );
+            if let Some(diff) = pretty_diff(&self.0, arg, "") {
+                error.push('\n');
+                error.push_str(&diff);
+            }
+            Err(error)
         }
     }
+
+    fn is_match(&self, arg: &Arg) -> bool {
+        self.0.matches(arg)
+    }
+
+    fn mismatched_args(&self, arg: &Arg) -> Option<Vec<usize>> {
+        Some(if self.0.matches(arg) { vec![] } else { vec![0] })
+    }
+}
+
+/// Diffs the pretty-printed argument against what an equality
+/// matcher expected, if either of them spans multiple lines.
+fn pretty_diff<Arg: fmt::Debug>(
+    matcher: &impl ArgMatcher<Arg>,
+    arg: &Arg,
+    indent: &str,
+) -> Option<String> {
+    let expected = matcher.pretty_expected()?;
+    let actual = format!("{:#?}", arg);
+    if !expected.contains('\n') && !actual.contains('\n') {
+        return None;
+    }
+
+    Some(format!(
+        "{}Diff (- expected, + actual):\n{}",
+        indent,
+        diff::lines(&expected, &actual, indent)
+    ))
 }
 
 // (1,2,3,..) => (true, true, true,..)
@@ -93,10 +162,11 @@ macro_rules! tuple {
                 /// Succeeds if every argument matches its corresponding [`ArgMatcher`].
                 fn matches(&self, ($([<a $idx>]),+): &($([<A $idx>],)+)) -> Result<(), String> {
                     let ($([<am $idx>]),+) = &self;
-                    let matches = match ($([<am $idx>].matches([<a $idx>])),+) {
+                    let ($([<m $idx>]),+) = match ($([<am $idx>].matches([<a $idx>])),+) {
                         trues!($($idx),+) => return Ok(()),
-                        ($([<a $idx>]),+) => [$([<a $idx>]),+],
+                        mismatch => mismatch,
                     };
+                    let matches = [$([<m $idx>]),+];
                     let expected = [
                         $([<am $idx>].to_string()),+
                     ];
@@ -122,15 +192,26 @@ macro_rules! tuple {
 
                     actual.reverse();
 
+                    let mut diffs = [
+                        $(if [<m $idx>] { None } else { pretty_diff([<am $idx>], [<a $idx>], "    ") }),+
+                    ];
+
                     let argument_errors: Vec<_> = matches
                         .iter()
                         .enumerate()
                         .filter_map(|(i, &passed)| if passed { None } else { Some(i) })
-                        .map(|pos| format!("  Argument {}:
+                        .map(|pos| {
+                            let mut error = format!("  Argument {}:
     Expected: {}
     Actual:   {}",
-                            pos, expected[pos], actual[pos]
-                        ))
+                                pos, expected[pos], actual[pos]
+                            );
+                            if let Some(diff) = diffs[pos].take() {
+                                error.push('\n');
+                                error.push_str(&diff);
+                            }
+                            error
+                        })
                         .collect();
 
                     let argument_errors = argument_errors.join("\n");
@@ -145,6 +226,23 @@ macro_rules! tuple {
                         expected, actual, argument_errors
                     ))
                 }
+
+                fn is_match(&self, ($([<a $idx>]),+): &($([<A $idx>],)+)) -> bool {
+                    let ($([<am $idx>]),+) = &self;
+                    $([<am $idx>].matches([<a $idx>]))&&+
+                }
+
+                fn mismatched_args(&self, ($([<a $idx>]),+): &($([<A $idx>],)+)) -> Option<Vec<usize>> {
+                    let ($([<am $idx>]),+) = &self;
+                    let matches = [$([<am $idx>].matches([<a $idx>])),+];
+                    Some(
+                        matches
+                            .iter()
+                            .enumerate()
+                            .filter_map(|(i, &passed)| if passed { None } else { Some(i) })
+                            .collect(),
+                    )
+                }
             }
         }
         peel! { $($idx,)+ }
@@ -1,14 +1,29 @@
 pub mod stub;
 
 mod store;
+pub(crate) mod type_id;
 mod unchecked;
 
 use std::{
     fmt::{self, Formatter},
-    sync::Mutex,
+    panic::{self, AssertUnwindSafe},
+    sync::{
+        atomic::{AtomicUsize, Ordering},
+        Arc, Condvar, Mutex, MutexGuard, PoisonError, Weak,
+    },
+    task::{Poll, Waker},
+    time::Duration,
 };
 
-pub use self::{store::Store, stub::Stub};
+use crate::{arg_debug::Input, matcher::InvocationMatcher, sync::CallGate};
+
+pub use self::{
+    store::{MethodId, Store},
+    stub::{Answer, Output, Start, Stub},
+};
+
+/// How many of the most recent invocations a failure lists
+const HISTORY_LEN: usize = 5;
 
 /// A function mock
 ///
@@ -16,7 +31,90 @@ pub use self::{store::Store, stub::Stub};
 /// inputs and output typed.
 pub struct Mock<'stub, I, O> {
     fn_name: &'static str,
-    stubs: Vec<Mutex<Stub<'stub, I, O>>>,
+    stubs: Vec<Stub<'stub, I, O>>,
+    /// Invocations that started, including the ones in flight. Also
+    /// the index of the next invocation.
+    started: AtomicUsize,
+    /// Number of watchers, so invocations only lock `calls` to match
+    /// them if there are any
+    watchers: AtomicUsize,
+    recent: Recent<I>,
+    /// Panic message of the latest invocation whose stub panicked
+    panicked: Mutex<Option<String>>,
+    calls: Mutex<Calls<I>>,
+    // notified after every invocation
+    called: Condvar,
+}
+
+/// An invocation that claimed a stub to answer it
+///
+/// The invocation is recorded once it is dropped, whether it ran or
+/// not.
+pub struct Invocation<'m, 'stub, I, O> {
+    mock: &'m Mock<'stub, I, O>,
+    stub: &'m Stub<'stub, I, O>,
+    /// Counts of the watchers matching the input
+    watched: Vec<Arc<AtomicUsize>>,
+}
+
+/// Gives an answer back to its stub once it ran, even if it panicked
+struct GiveBack<'s, 'stub, I, O> {
+    stub: &'s Stub<'stub, I, O>,
+    answer: Answer<'stub, I, O>,
+    /// Whether the stub expects the answer back
+    give_back: bool,
+}
+
+impl<I, O> Drop for GiveBack<'_, '_, I, O> {
+    fn drop(&mut self) {
+        if !self.give_back {
+            return;
+        }
+        let answer = std::mem::replace(&mut self.answer, Answer::Exhausted);
+        self.stub.give_back(answer);
+    }
+}
+
+/// Who is waiting on the invocations of a mock
+struct Calls<I> {
+    watchers: Vec<Watcher<I>>,
+    /// Futures waiting for the next invocation
+    wakers: Vec<Waker>,
+}
+
+/// Counts the invocations that match its matcher
+struct Watcher<I> {
+    /// `None` matches all invocations
+    matcher: Option<Box<dyn InvocationMatcher<I> + Send>>,
+    /// Index of the first invocation it counts, the prior ones were
+    /// counted when it was created
+    from: usize,
+    /// Dropped once nobody waits on the count
+    count: Weak<AtomicUsize>,
+}
+
+/// The invocations of a mock
+struct Recent<I> {
+    /// Number of recorded invocations
+    total: AtomicUsize,
+    /// Inputs of the latest invocations, if they could be kept, with
+    /// their index. Each invocation keeps its input in the slot at its
+    /// index when it starts, so concurrent invocations rarely contend
+    /// for the same lock. There is one more slot than is listed so a
+    /// failed invocation does not replace the oldest one it lists.
+    kept: [Mutex<Option<(usize, I)>>; HISTORY_LEN + 1],
+}
+
+/// The invocations a mock has seen
+#[derive(Debug, Clone, Default)]
+pub struct History {
+    /// Number of invocations since the mock was created
+    pub total: usize,
+    /// Debug representation of the arguments of the latest
+    /// invocations, oldest first. Empty if they could not be kept.
+    pub recent: Vec<String>,
+    /// Panic message of the latest invocation whose stub panicked
+    pub panicked: Option<String>,
 }
 
 impl<'stub, I, O> Mock<'stub, I, O> {
@@ -25,50 +123,324 @@ impl<'stub, I, O> Mock<'stub, I, O> {
         Self {
             fn_name,
             stubs: vec![],
+            started: AtomicUsize::new(0),
+            watchers: AtomicUsize::new(0),
+            recent: Recent {
+                total: AtomicUsize::new(0),
+                kept: [(); HISTORY_LEN + 1].map(|_| Mutex::new(None)),
+            },
+            panicked: Mutex::new(None),
+            calls: Mutex::new(Calls {
+                watchers: vec![],
+                wakers: vec![],
+            }),
+            called: Condvar::new(),
         }
     }
 
-    /// Attempts to invoke the mock
+    /// Starts an invocation of the mock
     ///
-    /// Checks the given input against the stored stubs, invoking the
-    /// first stub whose invocation matcher suceeds for the
-    /// inputs. The stubs are checked in reverse insertion order such
-    /// that the last inserted stub is the first attempted
-    /// one. Returns an error if no stub is found for the given input.
-    pub fn call(&self, mut input: I) -> Result<O, InvocationError> {
-        let mut errors = vec![];
+    /// Checks the given input against the stored stubs, claiming the
+    /// first stub whose invocation matcher suceeds for the inputs.
+    /// The stubs are checked in reverse insertion order such that the
+    /// last inserted stub is the first attempted one. Returns an error
+    /// if no stub is found for the given input, or if the first
+    /// matching stub expected to never be called.
+    ///
+    /// The claimed stub answers once the returned invocation is
+    /// [run](Invocation::run), after the caller passed its
+    /// [gate](Invocation::gate). `describe` formats the input, and
+    /// its previous ones, only if the invocation fails.
+    pub fn start(
+        &self,
+        input: &I,
+        describe: Input<I>,
+    ) -> Result<Invocation<'_, 'stub, I, O>, InvocationError> {
+        let kept = describe.keep.map(|keep| keep(input));
+        let (watched, index) = self.watching(input, kept);
 
         for stub in self.stubs.iter().rev() {
-            match stub.lock().unwrap().call(input) {
-                Err((i, e)) => {
-                    errors.push(e);
-                    input = i
-                }
-                Ok(o) => return Ok(o),
+            // earlier stubs may not answer a call that was forbidden
+            if stub.forbids(input) {
+                let errors = vec![stub.error(input)];
+                let history = self.history(describe.format, index);
+                self.record(watched);
+                return Err(InvocationError::Stub { errors, history });
+            }
+
+            if stub.claim(input) {
+                return Ok(Invocation {
+                    mock: self,
+                    stub,
+                    watched,
+                });
             }
         }
 
-        Err(if errors.is_empty() {
-            InvocationError::NeverStubbed
-        } else {
-            InvocationError::Stub(errors)
-        })
+        let history = self.history(describe.format, index);
+        self.record(watched);
+
+        if self.stubs.is_empty() {
+            return Err(InvocationError::NeverStubbed);
+        }
+
+        // only pay for the error messages once the call has failed
+        let mut errors: Vec<_> = self
+            .stubs
+            .iter()
+            .rev()
+            .map(|stub| stub.error(input))
+            .collect();
+
+        // lead with the stubs that came closest to matching
+        errors.sort_by_key(|e| e.mismatched_args().map_or(usize::MAX, |args| args.len()));
+
+        Err(InvocationError::Stub { errors, history })
     }
 
     /// Adds a new stub for the mocked function
     pub fn add_stub(&mut self, stub: Stub<'stub, I, O>) {
-        self.stubs.push(Mutex::new(stub))
+        self.stubs.push(stub)
+    }
+
+    /// Removes the stubs created in the scope at `depth` or deeper,
+    /// explaining which of them were called fewer times than expected
+    pub fn close_scope(&mut self, depth: usize) -> Vec<String> {
+        let mut unmet = vec![];
+        let fn_name = self.fn_name;
+        self.stubs.retain(|stub| {
+            if stub.scope() < depth {
+                return true;
+            }
+            unmet.extend(unmet_expectation(fn_name, stub));
+            false
+        });
+        unmet
     }
 
     pub fn name(&self) -> &'static str {
         self.fn_name
     }
+
+    /// Explains which stubs were called fewer times than expected
+    pub fn unmet_expectations(&self) -> Vec<String> {
+        self.stubs
+            .iter()
+            .filter_map(|stub| unmet_expectation(self.fn_name, stub))
+            .collect()
+    }
+
+    /// Number of invocations, including the ones that found no stub
+    pub fn calls(&self) -> usize {
+        self.recent.total.load(Ordering::SeqCst)
+    }
+
+    /// Starts counting invocations that match `matcher`
+    ///
+    /// Without a matcher, counts all invocations since the mock was
+    /// created, including the ones in flight during this call. Inputs
+    /// are matched as their invocations start, so with a matcher only
+    /// the invocations that start after this call are counted. The
+    /// count is kept for as long as the returned handle is alive.
+    pub fn watch(&self, matcher: Option<Box<dyn InvocationMatcher<I> + Send>>) -> Arc<AtomicUsize> {
+        let mut calls = lock(&self.calls);
+        // invocations that start from now on see the watcher
+        self.watchers.fetch_add(1, Ordering::SeqCst);
+        let from = self.started.load(Ordering::SeqCst);
+        let previous = if matcher.is_some() { 0 } else { from };
+        let count = Arc::new(AtomicUsize::new(previous));
+        calls.watchers.push(Watcher {
+            matcher,
+            from,
+            count: Arc::downgrade(&count),
+        });
+        count
+    }
+
+    /// Blocks until `count` reaches `target` or the timeout elapses
+    ///
+    /// Returns whether the target was reached.
+    pub fn wait_for(&self, count: &AtomicUsize, target: usize, timeout: Duration) -> bool {
+        let calls = lock(&self.calls);
+        let (_calls, result) = self
+            .called
+            .wait_timeout_while(calls, timeout, |_| count.load(Ordering::SeqCst) < target)
+            .unwrap_or_else(PoisonError::into_inner);
+        !result.timed_out()
+    }
+
+    /// Checks whether `count` reached `target`, waking `waker` on
+    /// the next invocation if not
+    pub fn poll_for(&self, count: &AtomicUsize, target: usize, waker: &Waker) -> Poll<()> {
+        let mut calls = lock(&self.calls);
+        if count.load(Ordering::SeqCst) >= target {
+            return Poll::Ready(());
+        }
+        if !calls.wakers.iter().any(|w| w.will_wake(waker)) {
+            calls.wakers.push(waker.clone());
+        }
+        Poll::Pending
+    }
+
+    /// Starts an invocation, keeping its input if given
+    ///
+    /// Returns the counts of the watchers matching the input, and the
+    /// index of the invocation.
+    fn watching(&self, input: &I, kept: Option<I>) -> (Vec<Arc<AtomicUsize>>, usize) {
+        let index = self.started.fetch_add(1, Ordering::SeqCst);
+        if let Some(kept) = kept {
+            *lock(self.recent.slot(index)) = Some((index, kept));
+        }
+        // only match the input if someone is waiting on it
+        if self.watchers.load(Ordering::SeqCst) == 0 {
+            return (vec![], index);
+        }
+
+        let mut calls = lock(&self.calls);
+        calls.watchers.retain(|w| w.count.strong_count() > 0);
+        self.watchers.store(calls.watchers.len(), Ordering::SeqCst);
+        let watched = calls
+            .watchers
+            .iter()
+            .filter(|w| index >= w.from)
+            .filter(|w| w.matcher.as_ref().map_or(true, |m| m.is_match(input)))
+            .filter_map(|w| w.count.upgrade())
+            .collect();
+        (watched, index)
+    }
+
+    /// `index` is the index of the failed invocation, the kept inputs
+    /// of the ones prior to it are listed
+    fn history(&self, format: fn(&I) -> String, index: usize) -> Box<History> {
+        // slots replaced by later invocations are skipped
+        let recent = (index.saturating_sub(HISTORY_LEN)..index)
+            .filter_map(|i| match &*lock(self.recent.slot(i)) {
+                Some((kept, input)) if *kept == i => Some(format(input)),
+                _ => None,
+            })
+            .collect();
+
+        Box::new(History {
+            // also counts the invocations in flight
+            total: index,
+            recent,
+            panicked: lock(&self.panicked).clone(),
+        })
+    }
+
+    fn record(&self, watched: Vec<Arc<AtomicUsize>>) {
+        self.recent.total.fetch_add(1, Ordering::SeqCst);
+
+        // anyone waiting on invocations registered a watcher first
+        if self.watchers.load(Ordering::SeqCst) == 0 {
+            return;
+        }
+
+        let mut calls = lock(&self.calls);
+        for count in watched {
+            count.fetch_add(1, Ordering::SeqCst);
+        }
+        let wakers = std::mem::take(&mut calls.wakers);
+        drop(calls);
+
+        self.called.notify_all();
+        wakers.into_iter().for_each(Waker::wake);
+    }
+}
+
+impl<I> Recent<I> {
+    /// Slot of the invocation at `index`
+    fn slot(&self, index: usize) -> &Mutex<Option<(usize, I)>> {
+        &self.kept[index % self.kept.len()]
+    }
+}
+
+impl<'m, 'stub, I, O> Invocation<'m, 'stub, I, O> {
+    /// Gate of the claimed stub, which the caller passes before
+    /// running the invocation
+    pub fn gate(&self) -> Option<&'m CallGate> {
+        self.stub.gate()
+    }
+
+    /// Runs the answer of the claimed stub
+    ///
+    /// Waits for the answer if another thread is running it. Returns
+    /// an error if the answer is running an outer call on this thread.
+    pub fn run(self, input: I) -> Result<Output<'stub, O>, InvocationError> {
+        let stub = self.stub;
+        let taken = match stub.start() {
+            Start::Recursive => {
+                return Err(InvocationError::Recursive {
+                    location: stub.location(),
+                })
+            }
+            Start::Taken(taken) => taken,
+        };
+
+        let mut running = GiveBack {
+            stub,
+            answer: taken.answer,
+            give_back: taken.give_back,
+        };
+        let output = panic::catch_unwind(AssertUnwindSafe(|| running.answer.run(input)));
+        drop(running);
+
+        match output {
+            Ok(output) => Ok(output),
+            Err(payload) => {
+                // remembered to explain later failures of the mock
+                *lock(&self.mock.panicked) = Some(panic_message(&*payload));
+                panic::resume_unwind(payload)
+            }
+        }
+    }
+}
+
+impl<I, O> Drop for Invocation<'_, '_, I, O> {
+    fn drop(&mut self) {
+        self.mock.record(std::mem::take(&mut self.watched));
+    }
+}
+
+/// Locks the mutex even if it was poisoned
+///
+/// Stubs and matchers run user code that may panic. No lock is held
+/// while a mock is in an inconsistent state so it can keep being used.
+fn lock<T>(mutex: &Mutex<T>) -> MutexGuard<'_, T> {
+    mutex.lock().unwrap_or_else(PoisonError::into_inner)
+}
+
+/// Explains why `stub` was called fewer times than expected, if it was
+fn unmet_expectation<I, O>(fn_name: &str, stub: &Stub<'_, I, O>) -> Option<String> {
+    let unmet = stub.unmet_expectation()?;
+    Some(format!("{}: `{}` {}", stub.location(), fn_name, unmet))
+}
+
+fn panic_message(payload: &(dyn std::any::Any + Send)) -> String {
+    if let Some(message) = payload.downcast_ref::<&str>() {
+        message.to_string()
+    } else if let Some(message) = payload.downcast_ref::<String>() {
+        message.clone()
+    } else {
+        "<non-string panic payload>".to_string()
+    }
 }
 
 #[derive(Debug)]
 pub enum InvocationError {
     NeverStubbed,
-    Stub(Vec<stub::Error>),
+    /// The stub that matched is answering an outer call on the same
+    /// thread
+    Recursive {
+        /// Where the stub was created
+        location: &'static std::panic::Location<'static>,
+    },
+    Stub {
+        /// Why each stub failed, closest matches first
+        errors: Vec<stub::Error>,
+        /// Invocations prior to the failed one
+        history: Box<History>,
+    },
 }
 
 impl<I, O> fmt::Debug for Mock<'_, I, O> {
@@ -1,13 +1,54 @@
-use std::collections::HashMap;
+use std::{
+    any::TypeId,
+    collections::HashMap,
+    fmt::{self, Formatter},
+    sync::Arc,
+};
 
 use crate::InvocationError;
 
-use super::{unchecked::Unchecked, Mock};
+use super::{type_id, unchecked::Unchecked, Mock};
 
-#[derive(Debug)]
 pub struct Store<'stub> {
     pub struct_name: &'static str,
-    stubs: HashMap<usize, Unchecked<'stub>>,
+    stubs: HashMap<MethodId, Unchecked<'stub>>,
+    /// Number of scopes open on the mock
+    scopes: usize,
+    /// Values that stubs hand out references to
+    kept: Vec<Arc<dyn Send + Sync>>,
+    /// Whether unmet expectations panic when the store is dropped
+    check_on_drop: bool,
+}
+
+/// Identifies a mocked method within its mock store
+///
+/// Generated by [`#[methods]`](crate::methods) for each method and
+/// each instantiation of its generics. Function pointers are not used
+/// as identities since the linker may merge identical functions, nor
+/// type names since different types may share a name, e.g. closures
+/// defined within the same function.
+#[doc(hidden)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct MethodId {
+    name: &'static str,
+    /// Type of the tuple of the method's generic type parameters,
+    /// with their lifetimes erased
+    generics: TypeId,
+}
+
+impl MethodId {
+    /// Identifies the method `name` instantiated with the generic type
+    /// parameters `G`, as a tuple
+    pub fn new<G: ?Sized>(name: &'static str) -> Self {
+        MethodId {
+            name,
+            generics: type_id::of::<G>(),
+        }
+    }
+
+    pub fn name(&self) -> &'static str {
+        self.name
+    }
 }
 
 impl<'stub> Store<'stub> {
@@ -15,44 +56,163 @@ impl<'stub> Store<'stub> {
         Store {
             struct_name,
             stubs: HashMap::new(),
+            scopes: 0,
+            kept: vec![],
+            check_on_drop: true,
+        }
+    }
+
+    /// Creates a store owned by a thread's registry of associated
+    /// functions
+    ///
+    /// Its expectations are not checked when it is dropped since it
+    /// may be dropped by a thread-local destructor, where a panic
+    /// aborts the process.
+    pub fn registered(struct_name: &'static str) -> Self {
+        let mut store = Store::new(struct_name);
+        store.check_on_drop = false;
+        store
+    }
+
+    /// Keeps `value` alive for as long as the store, returning a
+    /// pointer to it
+    ///
+    /// Stubs may hand out references to it, even once they are
+    /// exhausted and dropped.
+    pub fn keep<T: Send + Sync + 'static>(&mut self, value: T) -> *const T {
+        let value = Arc::new(value);
+        let pointer = Arc::as_ptr(&value);
+        self.kept.push(value);
+        pointer
+    }
+
+    /// Opens a scope for stubs, returning its depth
+    pub fn open_scope(&mut self) -> usize {
+        self.scopes += 1;
+        self.scopes
+    }
+
+    /// Closes the scope at `depth`, removing the stubs created in it
+    /// and in any scope nested within it
+    ///
+    /// # Panics
+    ///
+    /// Panics if any of the removed stubs was called fewer times than
+    /// expected, unless the thread is already panicking.
+    pub fn close_scope(&mut self, depth: usize) {
+        let mut unmet: Vec<_> = self
+            .stubs
+            .values_mut()
+            .flat_map(|mock| mock.close_scope(depth))
+            .collect();
+        self.scopes = depth - 1;
+
+        if std::thread::panicking() {
+            return;
+        }
+        unmet.sort();
+        if let Some(report) = self.report(unmet) {
+            panic!("{}", report);
         }
     }
 
+    /// Whether any scope is open on the mock
+    pub fn is_scoped(&self) -> bool {
+        self.scopes > 0
+    }
+
+    /// Whether `method` was ever stubbed
+    pub fn is_stubbed(&self, method: MethodId) -> bool {
+        self.stubs.contains_key(&method)
+    }
+
+    /// Number of invocations of the stubbed methods
+    pub fn calls(&self) -> usize {
+        self.stubs.values().map(Unchecked::calls).sum()
+    }
+
+    /// Explains which stubs were called fewer times than expected,
+    /// sorted
+    pub fn unmet_expectations(&self) -> Vec<String> {
+        let mut unmet: Vec<_> = self
+            .stubs
+            .values()
+            .flat_map(|mock| mock.unmet_expectations())
+            .collect();
+        unmet.sort();
+        unmet
+    }
+
+    /// Reports the stubs that were called fewer times than expected,
+    /// if any
+    pub fn unmet_report(&self) -> Option<String> {
+        self.report(self.unmet_expectations())
+    }
+
+    fn report(&self, unmet: Vec<String>) -> Option<String> {
+        if unmet.is_empty() {
+            return None;
+        }
+
+        Some(format!(
+            "faux: `{}` had stubs that were not called enough times:\n✗ {}",
+            self.struct_name,
+            unmet.join("\n✗ ")
+        ))
+    }
+
     /// Returns a mutable reference to a [`Mock`] for a given function
     ///
     /// If the given function has not yet been mocked, an empty mock
     /// is created for the function.
+    ///
+    /// `_id` is only used to infer the types of the mock.
     pub fn get_mut<R, I, O>(
         &mut self,
-        id: fn(R, I) -> O,
-        fn_name: &'static str,
+        _id: fn(R, I) -> O,
+        method: MethodId,
     ) -> &mut Mock<'stub, I, O> {
-        let mock = self.stubs.entry(id as usize).or_insert_with(|| {
-            let mock: Mock<I, O> = Mock::new(fn_name);
+        let mock = self.stubs.entry(method).or_insert_with(|| {
+            let mock: Mock<I, O> = Mock::new(method.name);
             mock.into()
         });
 
+        // the lifetimes of the stubs added through the returned mock
+        // are enforced by the bounds on the methods of `When`
         let mock = unsafe { mock.as_typed_mut() };
-        assert_name(mock, fn_name);
+        assert_name(mock, method.name);
         mock
     }
 
     /// Returns a reference to a [`Mock`] for a given function
     ///
-    /// `None` is returned if the function was never mocked
+    /// An error is returned if the function was never mocked. `_id`
+    /// is only used to infer the types of the mock.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the mock was created with different input (I) or
+    /// output (O) types.
+    ///
+    /// # Safety
+    ///
+    /// Only the types of the mock are checked, not their lifetimes.
+    /// The caller must ensure that the lifetimes of `I` and `O` are
+    /// the ones the mock was stubbed with, which generated code does
+    /// by deriving both from the same method signature.
     pub unsafe fn get<R, I, O>(
         &self,
-        id: fn(R, I) -> O,
-        fn_name: &'static str,
+        _id: fn(R, I) -> O,
+        method: MethodId,
         generics: &'static str,
     ) -> Result<&Mock<'stub, I, O>, InvocationError> {
-        match self.stubs.get(&(id as usize)).map(|m| m.as_typed()) {
+        match self.stubs.get(&method).map(|m| m.as_typed()) {
             Some(mock) => {
-                assert_name(mock, fn_name);
+                assert_name(mock, method.name);
                 Ok(mock)
             }
             None => Err(InvocationError {
-                fn_name,
+                fn_name: method.name,
                 struct_name: self.struct_name,
                 generics,
                 stub_error: super::InvocationError::NeverStubbed,
@@ -61,6 +221,30 @@ impl<'stub> Store<'stub> {
     }
 }
 
+impl fmt::Debug for Store<'_> {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        f.debug_struct("Store")
+            .field("struct_name", &self.struct_name)
+            .field("stubs", &self.stubs)
+            .field("scopes", &self.scopes)
+            .finish_non_exhaustive()
+    }
+}
+
+impl Drop for Store<'_> {
+    /// Panics if any stub was called fewer times than expected
+    fn drop(&mut self) {
+        // do not pile on a panic that is already unwinding
+        if !self.check_on_drop || std::thread::panicking() {
+            return;
+        }
+
+        if let Some(report) = self.unmet_report() {
+            panic!("{}", report);
+        }
+    }
+}
+
 fn assert_name<I, O>(mock: &Mock<I, O>, fn_name: &'static str) {
     assert_eq!(
         mock.name(),
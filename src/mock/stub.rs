@@ -1,52 +1,132 @@
 use std::{
     fmt::{self, Formatter},
-    num::NonZeroUsize,
+    future::Future,
+    panic::Location,
+    pin::Pin,
+    sync::{
+        atomic::{AtomicUsize, Ordering},
+        Arc, Condvar, Mutex, PoisonError,
+    },
+    thread::{self, ThreadId},
 };
 
-use crate::matcher::InvocationMatcher;
+use crate::{matcher::InvocationMatcher, sync::CallGate, when::Times};
+
+use super::lock;
 
+/// A stub of a mock
+///
+/// Invoking a stub is mostly lock-free: the matcher is shared between
+/// threads and calls are counted atomically. A shared answer is read
+/// without locking, so concurrent calls run it at the same time.
+/// Other answers are locked while they are taken out or given back,
+/// and run one call at a time. See [`Stub::start`].
 pub struct Stub<'a, I, O> {
-    matcher: Box<dyn InvocationMatcher<I> + Send>,
-    answer: Answer<'a, I, O>,
+    matcher: Box<dyn InvocationMatcher<I> + Send + Sync>,
+    answer: Held<'a, I, O>,
+    times: Times,
+    calls: AtomicUsize,
+    gate: Option<CallGate>,
+    /// Depth of the scope the stub was created in, 0 if unscoped
+    scope: usize,
+    location: &'static Location<'static>,
 }
 
 pub enum Answer<'a, I, O> {
     Exhausted,
+    /// Taken out by a call in flight on the given thread
+    Running(ThreadId),
     Once(Box<dyn FnOnce(I) -> O + Send + 'a>),
-    Many {
-        stub: Box<dyn FnMut(I) -> O + Send + 'a>,
-        times: Times,
+    Many(Box<dyn FnMut(I) -> O + Send + 'a>),
+    /// Run by concurrent calls at the same time
+    Shared(Arc<dyn Fn(I) -> O + Send + Sync + 'a>),
+    Async(Box<dyn FnMut(I) -> PendingOutput<'a, O> + Send + 'a>),
+}
+
+/// How a stub holds its answer
+enum Held<'a, I, O> {
+    /// Cloned out by every call
+    Shared(Arc<dyn Fn(I) -> O + Send + Sync + 'a>),
+    /// Taken out by one call at a time
+    Exclusive {
+        answer: Mutex<Answer<'a, I, O>>,
+        // notified when the answer is given back to the stub
+        given_back: Condvar,
     },
 }
 
-#[derive(Debug, Clone, Copy)]
-pub enum Times {
-    Always,
-    Times(NonZeroUsize),
+pub type PendingOutput<'a, O> = Pin<Box<dyn Future<Output = O> + Send + 'a>>;
+
+/// How a stub responded to a call that claimed it
+pub enum Start<'a, I, O> {
+    /// The stub is answering an outer call on the same thread
+    Recursive,
+    Taken(Taken<'a, I, O>),
+}
+
+/// The answer of a stub, taken out of it while a call runs it
+///
+/// Running the answer outside of the stub lets the stub be locked by
+/// other calls, including calls made by the answer itself.
+pub struct Taken<'a, I, O> {
+    pub answer: Answer<'a, I, O>,
+    /// Whether the answer must be given back, i.e. it is not shared
+    pub give_back: bool,
 }
 
+/// What a stub answers with
+pub enum Output<'a, O> {
+    /// The output itself
+    Ready(O),
+    /// A future that resolves to the output, only awaited by `async`
+    /// methods
+    Pending(PendingOutput<'a, O>),
+}
+
+/// Why a stub could not be invoked
 #[derive(Debug)]
-pub enum Error {
-    Exhausted,
-    NotMatched(String),
+pub struct Error {
+    /// Where the stub was created
+    pub location: &'static Location<'static>,
+    pub reason: Reason,
 }
 
-impl std::error::Error for Error {}
+#[derive(Debug)]
+pub enum Reason {
+    Exhausted,
+    /// The stub expected to never be called
+    Never,
+    NotMatched {
+        message: String,
+        /// Positions of the arguments that failed to match, if known
+        mismatched_args: Option<Vec<usize>>,
+    },
+}
 
-impl fmt::Display for Error {
-    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
-        match self {
-            Error::Exhausted => f.write_str("stub was exhausted"),
-            Error::NotMatched(error) => f.write_str(error),
+impl Error {
+    /// Positions of the arguments that kept the stub from being
+    /// invoked, if known
+    ///
+    /// An exhausted stub matched all of its arguments.
+    pub fn mismatched_args(&self) -> Option<&[usize]> {
+        match &self.reason {
+            Reason::Exhausted | Reason::Never => Some(&[]),
+            Reason::NotMatched {
+                mismatched_args, ..
+            } => mismatched_args.as_deref(),
         }
     }
 }
 
-impl Times {
-    pub fn decrement(self) -> Option<Self> {
-        match self {
-            Times::Always => Some(self),
-            Times::Times(n) => NonZeroUsize::new(n.get() - 1).map(Times::Times),
+impl std::error::Error for Error {}
+
+impl fmt::Display for Error {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        write!(f, "{}: ", self.location)?;
+        match &self.reason {
+            Reason::Exhausted => f.write_str("stub was exhausted"),
+            Reason::Never => f.write_str("stub was called but expected never"),
+            Reason::NotMatched { message, .. } => f.write_str(message),
         }
     }
 }
@@ -54,39 +134,202 @@ impl Times {
 impl<'a, I, O> Stub<'a, I, O> {
     pub fn new(
         stub: Answer<'a, I, O>,
-        matcher: impl InvocationMatcher<I> + Send + 'static,
+        times: Times,
+        matcher: impl InvocationMatcher<I> + Send + Sync + 'static,
+        location: &'static Location<'static>,
     ) -> Self {
+        let answer = match stub {
+            Answer::Shared(shared) => Held::Shared(shared),
+            stub => Held::Exclusive {
+                answer: Mutex::new(stub),
+                given_back: Condvar::new(),
+            },
+        };
+
         Stub {
             matcher: Box::new(matcher),
-            answer: stub,
+            answer,
+            times,
+            calls: AtomicUsize::new(0),
+            gate: None,
+            scope: 0,
+            location,
         }
     }
 
-    pub fn call(&mut self, input: I) -> Result<O, (I, Error)> {
-        // TODO: should the error message be different if the stub is also exhausted?
-        if let Err(e) = self.matcher.matches(&input) {
-            return Err((input, Error::NotMatched(e)));
+    /// Holds calls to the stub until the gate is released
+    pub fn gated(mut self, gate: Option<CallGate>) -> Self {
+        self.gate = gate;
+        self
+    }
+
+    /// Removes the stub once the scope at `depth` closes
+    pub fn scoped(mut self, depth: usize) -> Self {
+        self.scope = depth;
+        self
+    }
+
+    /// Depth of the scope the stub was created in, 0 if unscoped
+    pub fn scope(&self) -> usize {
+        self.scope
+    }
+
+    /// Counts a call to the stub if the input matches and it is not
+    /// exhausted, returning whether it did
+    ///
+    /// The call is counted before it passes the gate of the stub, so
+    /// other calls may not claim the stub in its stead. No
+    /// diagnostics are built here; use [`Stub::error`] once it is
+    /// known that no stub could be invoked.
+    pub fn claim(&self, input: &I) -> bool {
+        if !self.answers(input) {
+            return false;
         }
 
-        self.answer.call(input)
+        // other calls may have exhausted the stub since
+        self.calls
+            .fetch_update(Ordering::SeqCst, Ordering::SeqCst, |calls| {
+                match self.times.max {
+                    Some(max) if calls >= max => None,
+                    _ => Some(calls + 1),
+                }
+            })
+            .is_ok()
     }
-}
 
-impl<I, O> Answer<'_, I, O> {
-    fn call(&mut self, input: I) -> Result<O, (I, Error)> {
-        // no need to replace if we can keep decrementing
-        if let Answer::Many { stub, times } = self {
-            if let Some(decremented) = times.decrement() {
-                *times = decremented;
-                return Ok(stub(input));
+    /// Takes out the answer of the stub for a call that claimed it
+    ///
+    /// If the answer is running on another thread, waits for it to be
+    /// given back. If [`Taken::give_back`] is set, the answer must be
+    /// handed back using [`Stub::give_back`] once it has run. A
+    /// shared answer is cloned out without locking the stub.
+    pub fn start(&self) -> Start<'a, I, O> {
+        let (answer, given_back) = match &self.answer {
+            Held::Shared(shared) => {
+                return Start::Taken(Taken {
+                    answer: Answer::Shared(shared.clone()),
+                    give_back: false,
+                })
+            }
+            Held::Exclusive { answer, given_back } => (answer, given_back),
+        };
+
+        let mut answer = lock(answer);
+        loop {
+            match *answer {
+                Answer::Exhausted => {
+                    unreachable!("faux bug: claimed a stub whose answer was exhausted")
+                }
+                Answer::Running(thread) if thread == thread::current().id() => {
+                    return Start::Recursive
+                }
+                Answer::Running(_) => {
+                    answer = given_back
+                        .wait(answer)
+                        .unwrap_or_else(PoisonError::into_inner);
+                }
+                _ => break,
             }
         }
 
-        // otherwise replace it with an exhaust
+        let running = Answer::Running(thread::current().id());
+        let answer = std::mem::replace(&mut *answer, running);
+        Start::Taken(Taken {
+            answer,
+            give_back: true,
+        })
+    }
+
+    /// Gives back an answer taken by [`Stub::start`]
+    pub fn give_back(&self, answer: Answer<'a, I, O>) {
+        let (running, given_back) = match &self.answer {
+            Held::Shared(_) => return,
+            Held::Exclusive { answer, given_back } => (answer, given_back),
+        };
+        let mut running = lock(running);
+        if let Answer::Running(_) = *running {
+            *running = answer;
+        }
+        drop(running);
+        given_back.notify_all();
+    }
+
+    /// Where the stub was created
+    pub fn location(&self) -> &'static Location<'static> {
+        self.location
+    }
+
+    /// Explains why the stub was not called enough times, if so
+    pub fn unmet_expectation(&self) -> Option<String> {
+        let calls = self.calls.load(Ordering::SeqCst);
+        if calls >= self.times.min {
+            return None;
+        }
+
+        let called = match calls {
+            0 => "was never called".to_string(),
+            1 => "was called 1 time".to_string(),
+            n => format!("was called {} times", n),
+        };
+        Some(format!("expected {} but {}", self.times, called))
+    }
+
+    /// Gate that calls to the stub pass before it answers them
+    pub fn gate(&self) -> Option<&CallGate> {
+        self.gate.as_ref()
+    }
+
+    /// Whether the stub would answer the input, unless other calls
+    /// exhaust it first
+    pub fn answers(&self, input: &I) -> bool {
+        !self.is_exhausted() && self.matcher.is_match(input)
+    }
+
+    /// Whether the input matches a stub that expected to never be
+    /// called
+    pub fn forbids(&self, input: &I) -> bool {
+        self.times.max == Some(0) && self.matcher.is_match(input)
+    }
+
+    fn is_exhausted(&self) -> bool {
+        let calls = self.calls.load(Ordering::SeqCst);
+        self.times.max.map_or(false, |max| calls >= max)
+    }
+
+    /// Explains why the stub could not be invoked for this input
+    pub fn error(&self, input: &I) -> Error {
+        // TODO: should the error message be different if the stub is also exhausted?
+        let reason = match self.matcher.matches(input) {
+            Err(message) => Reason::NotMatched {
+                message,
+                mismatched_args: self.matcher.mismatched_args(input),
+            },
+            Ok(()) if self.times.max == Some(0) => Reason::Never,
+            Ok(()) => Reason::Exhausted,
+        };
+
+        Error {
+            location: self.location,
+            reason,
+        }
+    }
+}
+
+impl<'a, I, O> Answer<'a, I, O> {
+    /// Runs the answer
+    ///
+    /// A `Once` answer is consumed, leaving an exhausted one behind.
+    pub fn run(&mut self, input: I) -> Output<'a, O> {
+        match self {
+            Answer::Many(stub) => return Output::Ready(stub(input)),
+            Answer::Shared(stub) => return Output::Ready(stub(input)),
+            Answer::Async(stub) => return Output::Pending(stub(input)),
+            _ => {}
+        }
+
         match std::mem::replace(self, Answer::Exhausted) {
-            Answer::Exhausted => Err((input, Error::Exhausted)),
-            Answer::Once(stub) => Ok(stub(input)),
-            Answer::Many { mut stub, .. } => Ok(stub(input)),
+            Answer::Once(stub) => Output::Ready(stub(input)),
+            _ => unreachable!("faux bug: ran an answer that was not taken from its stub"),
         }
     }
 }
@@ -99,11 +342,22 @@ impl<I, O> fmt::Debug for Stub<'_, I, O> {
             .field(
                 "answer",
                 match &self.answer {
-                    Answer::Exhausted => &"Exhausted",
-                    Answer::Once(_) => &"Once",
-                    Answer::Many { .. } => &"Many",
+                    Held::Shared(_) => &"Shared",
+                    Held::Exclusive { answer, .. } => match &*lock(answer) {
+                        Answer::Exhausted => &"Exhausted",
+                        Answer::Running(_) => &"Running",
+                        Answer::Once(_) => &"Once",
+                        Answer::Many(_) => &"Many",
+                        Answer::Shared(_) => &"Shared",
+                        Answer::Async(_) => &"Async",
+                    },
                 },
             )
+            .field("times", &self.times)
+            .field("calls", &self.calls.load(Ordering::SeqCst))
+            .field("gate", &self.gate)
+            .field("scope", &self.scope)
+            .field("location", &self.location)
             .finish()
     }
 }
@@ -0,0 +1,34 @@
+//! Type identities that ignore lifetimes.
+//!
+//! [`TypeId::of`] requires its type to be `'static`, but mocked
+//! methods may take and return borrowed data. Erasing the lifetimes
+//! still tells apart any two types that differ in more than their
+//! lifetimes, such as two closures defined in the same function.
+
+use std::{any::TypeId, marker::PhantomData, mem};
+
+trait NonStaticAny {
+    fn get_type_id(&self) -> TypeId
+    where
+        Self: 'static;
+}
+
+impl<T: ?Sized> NonStaticAny for PhantomData<T> {
+    fn get_type_id(&self) -> TypeId
+    where
+        Self: 'static,
+    {
+        TypeId::of::<T>()
+    }
+}
+
+/// The [`TypeId`] of `T` with all of its lifetimes erased
+pub fn of<T: ?Sized>() -> TypeId {
+    let phantom = PhantomData::<T>;
+    let erased: &dyn NonStaticAny = &phantom;
+    // Safety: `TypeId::of` only reads the type, never a value of it,
+    // and lifetimes do not exist by the time the code runs, so the
+    // extended lifetime is never observed.
+    let erased: &(dyn NonStaticAny + 'static) = unsafe { mem::transmute(erased) };
+    erased.get_type_id()
+}
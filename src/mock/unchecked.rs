@@ -1,80 +1,150 @@
-//! THIS IS ONE OF THE MOST DANGEROUS PARTS OF `FAUX`. PROCEED WITH
-//! EXTREME CAUTION.
+//! Stores mocks of different types in the same collection.
+//!
+//! The mocks are boxed and their types erased. Anything that needs
+//! the types goes through functions instantiated with the original
+//! types when the mock was erased, and the types are checked again
+//! whenever the mock is retrieved.
 
-use std::fmt::{self, Formatter};
+use std::{
+    any::{self, TypeId},
+    fmt::{self, Formatter},
+    marker::PhantomData,
+    ptr::NonNull,
+};
 
-use super::Mock;
+use super::{type_id, Mock};
 
-/// Stores the a mock with its generics "erased"
+/// Stores a mock with its generics "erased"
 ///
 /// This allows different mocks to be saved in the same collections.
 /// Ideally we would use something like `std::any::Any` instead but
 /// dynamic casting only works on static types and we do not want to
-/// limit `faux` to only working with static inputs/outputs.
+/// limit `faux` to only working with static inputs/outputs. Instead,
+/// the input and output types are identified with their lifetimes
+/// erased and compared on retrieval.
 pub struct Unchecked<'stub> {
-    unsafe_mock: Mock<'stub, (), ()>,
-    debug_repr: String,
+    mock: NonNull<()>,
+    /// The `(I, O)` types of the mock, with their lifetimes erased
+    types: TypeId,
+    /// Name of the `(I, O)` types of the mock, for error messages
+    type_name: &'static str,
+    vtable: &'static VTable,
+    _mock: PhantomData<Mock<'stub, (), ()>>,
 }
 
+/// Functions instantiated with the original types of the mock
+struct VTable {
+    drop: unsafe fn(NonNull<()>),
+    debug: unsafe fn(NonNull<()>, &mut Formatter<'_>) -> fmt::Result,
+    unmet_expectations: unsafe fn(NonNull<()>) -> Vec<String>,
+    calls: unsafe fn(NonNull<()>) -> usize,
+    close_scope: unsafe fn(NonNull<()>, usize) -> Vec<String>,
+}
+
+trait HasVTable {
+    const VTABLE: VTable;
+}
+
+impl<'stub, I, O> HasVTable for Mock<'stub, I, O> {
+    const VTABLE: VTable = VTable {
+        drop: |mock| unsafe { drop(Box::from_raw(mock.cast::<Self>().as_ptr())) },
+        debug: |mock, f| unsafe { fmt::Debug::fmt(mock.cast::<Self>().as_ref(), f) },
+        unmet_expectations: |mock| unsafe { mock.cast::<Self>().as_ref().unmet_expectations() },
+        calls: |mock| unsafe { mock.cast::<Self>().as_ref().calls() },
+        close_scope: |mock, depth| unsafe { mock.cast::<Self>().as_mut().close_scope(depth) },
+    };
+}
+
+// Safety: `Mock` is `Send` and `Sync` regardless of its input and
+// output since it never holds onto its outputs and only keeps inputs
+// that are `Send + Sync + 'static`.
+unsafe impl Send for Unchecked<'_> {}
+unsafe impl Sync for Unchecked<'_> {}
+
 impl<'stub> Unchecked<'stub> {
     /// Returns a reference to the mock with its types re-added.
     ///
+    /// # Panics
+    ///
+    /// Panics if the mock was created with different input (I) or
+    /// output (O) types.
+    ///
     /// # Safety
     ///
-    /// This method is *extremely* unsafe. This is only safe if you
-    /// know precisely what the input (I), output (O) were of the
-    /// original [`Mock`] this came from.
+    /// Types are compared with their lifetimes erased, since they may
+    /// not be `'static`. This is only safe if the lifetimes of the
+    /// input and output match the ones of the original [`Mock`].
     pub unsafe fn as_typed<I, O>(&self) -> &Mock<'stub, I, O> {
-        // Might be safer to only transmute only the matcher and stub
-        // of each mock instead of the entire object. This works
-        // though, and I don't see any reason why it wouldn't but if
-        // we start seeing seg-faults this is a potential thing to
-        // change.
-        let mock = &self.unsafe_mock;
-        std::mem::transmute(mock)
+        self.assert_types::<I, O>();
+        self.mock.cast().as_ref()
     }
 
     /// Returns a mutable reference to the mock with its types
     /// re-added.
     ///
+    /// # Panics
+    ///
+    /// Panics if the mock was created with different input (I) or
+    /// output (O) types.
+    ///
     /// # Safety
     ///
-    /// This method is *extremely* unsafe. This is only safe if you
-    /// know precisely what the input (I), output (O) were of the
-    /// original [`Mock`] this came from.
+    /// Types are compared with their lifetimes erased, since they may
+    /// not be `'static`. This is only safe if the lifetimes of the
+    /// input and output match the ones of the original [`Mock`].
     pub unsafe fn as_typed_mut<I, O>(&mut self) -> &mut Mock<'stub, I, O> {
-        // Might be safer to only transmute only the matcher and stub
-        // of each mock instead of the entire object. This works
-        // though, and I don't see any reason why it wouldn't but if
-        // we start seeing seg-faults this is a potential thing to
-        // change.
-        let mock = &mut self.unsafe_mock;
-        std::mem::transmute(mock)
+        self.assert_types::<I, O>();
+        self.mock.cast().as_mut()
+    }
+
+    /// Explains which stubs of the mock were called fewer times than
+    /// expected
+    pub fn unmet_expectations(&self) -> Vec<String> {
+        unsafe { (self.vtable.unmet_expectations)(self.mock) }
+    }
+
+    /// Number of invocations of the mock
+    pub fn calls(&self) -> usize {
+        unsafe { (self.vtable.calls)(self.mock) }
+    }
+
+    /// Removes the stubs created in the scope at `depth` or deeper,
+    /// explaining which of them were called fewer times than expected
+    pub fn close_scope(&mut self, depth: usize) -> Vec<String> {
+        unsafe { (self.vtable.close_scope)(self.mock, depth) }
+    }
+
+    fn assert_types<I, O>(&self) {
+        assert!(
+            self.types == type_id::of::<(I, O)>(),
+            "faux bug: mock was stored with types `{}` but requested as `{}`",
+            self.type_name,
+            any::type_name::<(I, O)>()
+        );
+    }
+}
+
+impl Drop for Unchecked<'_> {
+    fn drop(&mut self) {
+        unsafe { (self.vtable.drop)(self.mock) }
     }
 }
 
 impl fmt::Debug for Unchecked<'_> {
     fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
-        f.write_str(&self.debug_repr)
+        unsafe { (self.vtable.debug)(self.mock, f) }
     }
 }
 
 impl<'stub, I, O> From<Mock<'stub, I, O>> for Unchecked<'stub> {
-    fn from(mock: Mock<I, O>) -> Self {
-        // we are about to lose all information about the mock so
-        // let's save its debug representation so we can print it as
-        // our own
-        let debug_repr = format!("{:?}", mock);
-        // Safety:
-        // The only posible actions on the returned `Saved` are:
-        // * as_checked_mut: already marked as `unsafe`
-        // * debug format: does not look into the unsafe fields
-        unsafe {
-            let unsafe_mock: Mock<_, _> = std::mem::transmute(mock);
-            Self {
-                unsafe_mock,
-                debug_repr,
-            }
+    fn from(mock: Mock<'stub, I, O>) -> Self {
+        let mock = NonNull::from(Box::leak(Box::new(mock)));
+        Unchecked {
+            mock: mock.cast(),
+            types: type_id::of::<(I, O)>(),
+            type_name: any::type_name::<(I, O)>(),
+            vtable: &<Mock<'stub, I, O> as HasVTable>::VTABLE,
+            _mock: PhantomData,
         }
     }
 }
@@ -0,0 +1,201 @@
+use std::{fmt, marker::PhantomData};
+
+use paste::paste;
+
+use crate::{arg_debug, matcher::AnyInvocation, Calls, Faux, MethodId, When};
+
+/// Creates a mock of a closure or function taking the arguments in
+/// the tuple `Args` and returning `O`.
+///
+/// Mocks code that is given a callback, e.g. `impl Fn(Request) ->
+/// Response`. The mock is stubbed like any method, using
+/// [`when!`](crate::when!) on its `call` method, and handed to the
+/// code under test as a closure using `as_fn` or `into_fn`.
+///
+/// Arguments are given to stubs and matchers the same way as for
+/// methods: a single argument by itself, and multiple arguments as a
+/// tuple. They must implement `Debug` and `Clone`, be `Send + Sync`,
+/// and own their data so the mock can list its previous calls when a
+/// call finds no stub.
+///
+/// # Examples
+///
+/// ```
+/// fn retry(attempts: u32, request: impl Fn(u32) -> Result<u32, String>) -> Option<u32> {
+///     (0..attempts).find_map(|attempt| request(attempt).ok())
+/// }
+///
+/// # fn main() {
+/// let mut request = faux::mock_fn::<(u32,), Result<u32, String>>();
+/// faux::when!(request.call(_)).then(|attempt| Err(format!("attempt {} failed", attempt)));
+/// faux::when!(request.call(2)).then_return(Ok(10));
+///
+/// assert_eq!(retry(3, request.as_fn()), Some(10));
+/// assert_eq!(request.call((2,)), Ok(10));
+/// # }
+/// ```
+///
+/// # Function pointers
+///
+/// Function pointers, e.g. `fn(u32) -> u32`, cannot carry a mock so
+/// a `MockFn` cannot be passed as one. Use
+/// [`#[function]`](crate::function) on the function passed instead.
+pub fn mock_fn<Args: FnArgs, O>() -> MockFn<Args, O> {
+    MockFn {
+        faux: Faux::new("MockFn"),
+        _fn: PhantomData,
+    }
+}
+
+/// A mock of a closure or function.
+///
+/// Created using [`mock_fn`].
+pub struct MockFn<Args, O> {
+    faux: Faux,
+    _fn: PhantomData<fn(Args) -> O>,
+}
+
+impl<Args: FnArgs, O> MockFn<Args, O> {
+    /// Calls the mock with its arguments as a tuple.
+    ///
+    /// # Panics
+    ///
+    /// Panics if no stub matches the arguments.
+    pub fn call(&self, args: Args) -> O {
+        // Safety: the id carries the types of the mock
+        let output = unsafe {
+            self.faux.call_stub(
+                id::<Args::Input, O>,
+                method(),
+                args.into_input(),
+                arg_debug::Input {
+                    format: Args::format,
+                    keep: Some(<Args::Input>::clone),
+                },
+                "",
+            )
+        };
+        match output {
+            Ok(o) => o,
+            Err(e) => panic!("{}", e),
+        }
+    }
+
+    #[doc(hidden)]
+    #[track_caller]
+    pub fn _when_call(&mut self) -> When<'_, (), Args::Input, O, AnyInvocation> {
+        When::new(id::<Args::Input, O>, method(), false, &mut self.faux)
+    }
+
+    #[doc(hidden)]
+    pub fn _calls_call(&self) -> Calls<'_, (), Args::Input, O> {
+        Calls::new(id::<Args::Input, O>, method(), &self.faux)
+    }
+}
+
+fn method() -> MethodId {
+    MethodId::new::<()>("call")
+}
+
+/// Only used to carry the types of the mock
+fn id<I, O>(_: (), _: I) -> O {
+    panic!("do not call this (faux::mock_fn::id)")
+}
+
+/// Arguments of a [`MockFn`], as a tuple
+pub trait FnArgs {
+    /// How the arguments are given to stubs and matchers: a single
+    /// argument by itself, or multiple arguments as a tuple
+    type Input: Clone + Send + Sync + 'static;
+
+    fn into_input(self) -> Self::Input;
+
+    /// Formats the input as a comma separated list of arguments
+    #[doc(hidden)]
+    fn format(input: &Self::Input) -> String;
+}
+
+impl FnArgs for () {
+    type Input = ();
+
+    fn into_input(self) {}
+
+    fn format(_: &()) -> String {
+        String::new()
+    }
+}
+
+impl<A: fmt::Debug + Clone + Send + Sync + 'static> FnArgs for (A,) {
+    type Input = A;
+
+    fn into_input(self) -> A {
+        self.0
+    }
+
+    fn format(input: &A) -> String {
+        arg_debug::format(&[input])
+    }
+}
+
+// implement FnArgs for tuples of multiple arguments
+macro_rules! fn_args {
+    ($($arg:ident),+) => {
+        paste! {
+            impl<$($arg: fmt::Debug + Clone + Send + Sync + 'static),+> FnArgs for ($($arg,)+) {
+                type Input = ($($arg,)+);
+
+                fn into_input(self) -> Self::Input {
+                    self
+                }
+
+                fn format(($([<$arg:lower>],)+): &Self::Input) -> String {
+                    arg_debug::format(&[$([<$arg:lower>]),+])
+                }
+            }
+        }
+    };
+}
+
+fn_args!(A, B);
+fn_args!(A, B, C);
+fn_args!(A, B, C, D);
+fn_args!(A, B, C, D, E);
+fn_args!(A, B, C, D, E, F);
+fn_args!(A, B, C, D, E, F, G);
+fn_args!(A, B, C, D, E, F, G, H);
+fn_args!(A, B, C, D, E, F, G, H, I);
+fn_args!(A, B, C, D, E, F, G, H, I, J);
+
+// convert a MockFn into closures of its arity
+macro_rules! as_fn {
+    ($($arg:ident),*) => (
+        paste! {
+            impl<$($arg: fmt::Debug + Clone + Send + Sync + 'static,)* O> MockFn<($($arg,)*), O> {
+                /// Borrows the mock as a closure.
+                pub fn as_fn(&self) -> impl Fn($($arg),*) -> O + '_ {
+                    move |$([<$arg:lower>]),*| self.call(($([<$arg:lower>],)*))
+                }
+
+                /// Converts the mock into a closure.
+                ///
+                /// Stub the mock before converting it since it can no
+                /// longer be stubbed.
+                pub fn into_fn(self) -> impl Fn($($arg),*) -> O {
+                    move |$([<$arg:lower>]),*| self.call(($([<$arg:lower>],)*))
+                }
+            }
+        }
+    );
+}
+
+as_fn!();
+as_fn!(A);
+as_fn!(A, B);
+as_fn!(A, B, C);
+as_fn!(A, B, C, D);
+as_fn!(A, B, C, D, E);
+as_fn!(A, B, C, D, E, F);
+as_fn!(A, B, C, D, E, F, G);
+as_fn!(A, B, C, D, E, F, G, H);
+as_fn!(A, B, C, D, E, F, G, H, I);
+as_fn!(A, B, C, D, E, F, G, H, I, J);
@@ -0,0 +1,150 @@
+use crate::Faux;
+
+/// A struct made mockable by [`#[create]`](crate::create).
+///
+/// Implemented by `#[create]` for every mockable struct so test
+/// helpers may be written for any mock. It requires a hidden
+/// supertrait that only `#[create]` is meant to implement, and whose
+/// methods only `faux` may call.
+///
+/// The [`MockStore`] of a mock only keeps track of stubbed methods, so
+/// [`MockStore::calls`] does not count calls to methods that were
+/// never stubbed.
+///
+/// # Examples
+///
+/// ```
+/// use faux::Mockable;
+///
+/// #[faux::create]
+/// pub struct UserService {}
+///
+/// #[faux::methods]
+/// impl UserService {
+///     pub fn exists(&self, id: u32) -> bool {
+///         /* implementation code */
+///         # panic!()
+///     }
+/// }
+///
+/// fn assert_no_calls(mock: &impl Mockable) {
+///     let store = mock.faux_store().expect("not a mock");
+///     assert_eq!(store.calls(), 0, "`{}` was called", store.struct_name());
+/// }
+///
+/// # fn main() {
+/// let mut service: UserService = Mockable::faux();
+/// assert!(service.is_faux());
+///
+/// faux::when!(service.exists).then_return(true);
+/// assert_no_calls(&service);
+/// # }
+/// ```
+///
+/// It is not implemented by hand:
+///
+/// ```compile_fail,E0277
+/// pub struct Handmade;
+///
+/// impl faux::Mockable for Handmade {
+///     const NAME: &'static str = "Handmade";
+///
+///     fn faux() -> Self {
+///         Handmade
+///     }
+///
+///     fn is_faux(&self) -> bool {
+///         true
+///     }
+///
+///     fn faux_store(&self) -> Option<faux::MockStore<'_>> {
+///         None
+///     }
+/// }
+/// ```
+pub trait Mockable: Sized + sealed::Sealed {
+    /// The name of the struct, as used in error messages.
+    const NAME: &'static str;
+
+    /// Creates a mock instance of the struct.
+    ///
+    /// Same as the `faux` associated function generated by
+    /// [`#[create]`](crate::create).
+    fn faux() -> Self;
+
+    /// Whether this is a mock instance, as opposed to a real one.
+    fn is_faux(&self) -> bool;
+
+    /// The store of the stubs of a mock instance, `None` for a real
+    /// instance.
+    fn faux_store(&self) -> Option<MockStore<'_>>;
+}
+
+#[doc(hidden)]
+pub mod sealed {
+    use crate::Faux;
+
+    /// Implemented by `#[create]` alongside
+    /// [`Mockable`](super::Mockable) to give `faux` mutable access to
+    /// the mock.
+    pub trait Sealed {
+        /// The mock, `None` for a real instance
+        ///
+        /// Takes a [`Token`] so only `faux` may call it.
+        fn faux_mut(&mut self, _: Token) -> Option<&mut Faux>;
+    }
+
+    /// Proof that [`Sealed::faux_mut`] is called by `faux`, which is
+    /// the only one that may create it
+    ///
+    /// ```compile_fail,E0423
+    /// use faux::sealed::{Sealed, Token};
+    ///
+    /// fn steal(mock: &mut impl faux::Mockable) -> Option<&mut faux::Faux> {
+    ///     mock.faux_mut(Token(()))
+    /// }
+    /// ```
+    #[derive(Debug)]
+    pub struct Token(());
+
+    /// The mock of `mock`, `None` for a real instance
+    pub(crate) fn faux_mut<M: Sealed>(mock: &mut M) -> Option<&mut Faux> {
+        mock.faux_mut(Token(()))
+    }
+}
+
+/// The stubs of a mock instance.
+///
+/// Obtained through [`Mockable::faux_store`]. Clones of a mock share
+/// their store.
+#[derive(Clone, Copy, Debug)]
+pub struct MockStore<'m> {
+    faux: &'m Faux,
+}
+
+impl<'m> MockStore<'m> {
+    #[doc(hidden)]
+    pub fn new(faux: &'m Faux) -> Self {
+        MockStore { faux }
+    }
+
+    /// The name of the mocked struct, as used in error messages.
+    pub fn struct_name(&self) -> &'static str {
+        self.faux.store.struct_name
+    }
+
+    /// Number of times the methods of the mock were called.
+    ///
+    /// Only methods that were stubbed are counted, including the
+    /// calls that found no suitable stub.
+    pub fn calls(&self) -> usize {
+        self.faux.store.calls()
+    }
+
+    /// Explains which stubs were called fewer times than expected.
+    ///
+    /// The mock panics with these explanations once it is dropped.
+    pub fn unmet_expectations(&self) -> Vec<String> {
+        self.faux.store.unmet_expectations()
+    }
+}
@@ -0,0 +1,151 @@
+use std::{marker::PhantomData, process, ptr, sync::Arc};
+
+use crate::{mock, sealed, Faux, Mockable};
+
+/// A scope in which stubs may borrow from the test.
+///
+/// Opened with [`scope`]. Pass it to [`When::in_scope`] to create a
+/// stub in it.
+///
+/// [`When::in_scope`]: crate::When::in_scope
+pub struct Scope<'env> {
+    /// Store of the mock the scope was opened on, only compared
+    store: *const mock::Store<'static>,
+    depth: usize,
+    // invariant so stubs cannot pick a shorter lifetime
+    _env: PhantomData<&'env mut &'env ()>,
+}
+
+impl Scope<'_> {
+    /// Whether the scope was opened on the mock of `store`
+    pub(crate) fn is_of(&self, store: &mock::Store<'static>) -> bool {
+        ptr::eq(self.store, store)
+    }
+
+    pub(crate) fn depth(&self) -> usize {
+        self.depth
+    }
+}
+
+/// Opens a scope on a mock in which its stubs may borrow from the
+/// test.
+///
+/// Stubs created with [`When::in_scope`] may capture and return
+/// references to anything that outlives the call to `scope`, without
+/// resorting to [`then_unchecked`]. They are removed from the mock
+/// once `f` returns, leaving the stubs created before or outside of
+/// the scope.
+///
+/// The mock is lent back to `f` to stub and use it. Not every method
+/// may be stubbed in a scope, see [`When::in_scope`].
+///
+/// # Panics
+///
+/// Panics if the mock is a real instance or if it was cloned. Cloning
+/// the mock while the scope is open panics as well since the clone
+/// could outlive the scope.
+///
+/// Once `f` returns, panics if any stub created in the scope was
+/// called fewer times than expected, like a mock does when dropped.
+///
+/// If the mock is moved out of the reference given to `f`, e.g. using
+/// [`std::mem::replace`], the process is aborted once `f` returns:
+/// its scoped stubs could no longer be removed.
+///
+/// # Examples
+///
+/// ```rust
+/// #[faux::create]
+/// pub struct Store {}
+///
+/// #[faux::methods]
+/// impl Store {
+///     pub fn get(&self, id: u32) -> &str {
+///       /* implementation code */
+///       # panic!()
+///     }
+///
+///     pub fn len(&self) -> usize {
+///       /* implementation code */
+///       # panic!()
+///     }
+/// }
+///
+/// fn main() {
+///   let mut store = Store::faux();
+///   faux::when!(store.len).then_return(0);
+///
+///   let value = String::from("scoped");
+///   faux::scope(&mut store, |s, store| {
+///       faux::when!(store.get(1)).in_scope(s).then(|_| value.as_str());
+///       faux::when!(store.len).in_scope(s).then(|_| value.len());
+///
+///       assert_eq!(store.get(1), "scoped");
+///       assert_eq!(store.len(), 6);
+///   });
+///
+///   // the scoped stubs were removed
+///   assert_eq!(store.len(), 0);
+/// }
+/// ```
+///
+/// [`When::in_scope`]: crate::When::in_scope
+/// [`then_unchecked`]: crate::When::then_unchecked
+pub fn scope<'env, M: Mockable, T>(mock: &mut M, f: impl FnOnce(&Scope<'env>, &mut M) -> T) -> T {
+    let faux =
+        sealed::faux_mut(mock).expect("faux: not allowed to open a scope on a real instance!");
+    let store = Arc::as_ptr(&faux.store);
+    let depth = faux
+        .unique_store()
+        .expect("faux: failed to get unique handle to mock. Scopes may only be opened on a mock prior to cloning it.")
+        .open_scope();
+
+    let scope = Scope {
+        store,
+        depth,
+        _env: PhantomData,
+    };
+    let mock: *mut M = mock;
+    let _close = Close {
+        scope: &scope,
+        mock,
+    };
+    // Safety: `mock` is not used again until `f` is done with it
+    f(&scope, unsafe { &mut *mock })
+}
+
+/// Closes a scope once `f` returned or unwound
+struct Close<'s, 'env, M: Mockable> {
+    scope: &'s Scope<'env>,
+    mock: *mut M,
+}
+
+impl<M: Mockable> Drop for Close<'_, '_, M> {
+    fn drop(&mut self) {
+        // Safety: `f` is done with the mock
+        let mock = unsafe { &mut *self.mock };
+        let store = sealed::faux_mut(mock)
+            .filter(|faux| ptr::eq(Arc::as_ptr(&faux.store), self.scope.store))
+            .and_then(Faux::unique_store);
+
+        match store {
+            Some(store) => store.close_scope(self.scope.depth),
+            None => {
+                // the stubs may keep answering with references to
+                // data that is about to be dropped
+                eprintln!("faux: a mock was moved out of its scope so its scoped stubs cannot be removed; aborting");
+                process::abort();
+            }
+        }
+    }
+}
+
+/// Implemented for the witness of a method whose stubs may borrow from
+/// a scope: `fn(I)` with its arguments `I` as written in the method.
+///
+/// Lifetimes elided from the arguments, even from a path such as
+/// `Cow<str>`, make the witness higher-ranked, e.g. `for<'a>
+/// fn(Cow<'a, str>)`, which never implements it.
+pub trait Scopable<I> {}
+
+impl<I> Scopable<I> for fn(I) {}
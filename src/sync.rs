@@ -0,0 +1,201 @@
+//! Tools to hold mocked calls in flight for concurrency tests.
+//!
+//! See [`CallGate`].
+
+use std::{
+    fmt::{self, Formatter},
+    future::Future,
+    pin::Pin,
+    sync::{Arc, Condvar, Mutex},
+    task::{Context, Poll, Waker},
+    time::{Duration, Instant},
+};
+
+/// Holds calls to stubbed methods until it is released.
+///
+/// Attach it to a stub using [`When::block_until`]. Calls to that
+/// stub register that they entered the gate and then wait for it to
+/// be released before the stub answers them. Blocking methods block their thread while `async`
+/// methods stay pending without blocking their executor. Once
+/// released, the gate lets every current and future call through.
+///
+/// This lets a test observe that a call is in flight, provoke a race
+/// deterministically, and then let the call finish.
+///
+/// Clones share the same gate.
+///
+/// # Examples
+///
+/// ```rust
+/// use std::{sync::Arc, thread};
+/// use faux::sync::CallGate;
+///
+/// #[faux::create]
+/// pub struct Foo {}
+///
+/// #[faux::methods]
+/// impl Foo {
+///     pub fn fetch(&self) -> u32 {
+///       /* implementation code */
+///       # panic!()
+///     }
+/// }
+///
+/// fn main() {
+///   let mut mock = Foo::faux();
+///   let gate = CallGate::new();
+///   faux::when!(mock.fetch).block_until(&gate).then_return(5);
+///
+///   let mock = Arc::new(mock);
+///   let fetching = {
+///     let mock = mock.clone();
+///     thread::spawn(move || mock.fetch())
+///   };
+///
+///   // the call is now in flight
+///   gate.wait_entered();
+///   gate.release();
+///   assert_eq!(fetching.join().unwrap(), 5);
+/// }
+/// ```
+///
+/// [`When::block_until`]: crate::When::block_until
+#[derive(Clone, Default)]
+pub struct CallGate {
+    inner: Arc<Inner>,
+}
+
+#[derive(Default)]
+struct Inner {
+    state: Mutex<State>,
+    changed: Condvar,
+}
+
+#[derive(Default)]
+struct State {
+    entered: usize,
+    released: bool,
+    wakers: Vec<Waker>,
+}
+
+impl CallGate {
+    /// Creates a closed gate.
+    pub fn new() -> Self {
+        CallGate::default()
+    }
+
+    /// Number of calls that have entered the gate.
+    ///
+    /// Includes calls that were let through after it was released.
+    pub fn entered(&self) -> usize {
+        self.inner.state.lock().unwrap().entered
+    }
+
+    /// Blocks until at least one call has entered the gate.
+    pub fn wait_entered(&self) {
+        let state = self.inner.state.lock().unwrap();
+        let _state = self
+            .inner
+            .changed
+            .wait_while(state, |state| state.entered == 0)
+            .unwrap();
+    }
+
+    /// Blocks until at least one call has entered the gate or the
+    /// timeout elapses.
+    ///
+    /// Returns whether a call entered.
+    pub fn wait_entered_timeout(&self, timeout: Duration) -> bool {
+        let deadline = Instant::now() + timeout;
+        let mut state = self.inner.state.lock().unwrap();
+        while state.entered == 0 {
+            let remaining = match deadline.checked_duration_since(Instant::now()) {
+                Some(remaining) => remaining,
+                None => return false,
+            };
+            state = self.inner.changed.wait_timeout(state, remaining).unwrap().0;
+        }
+        true
+    }
+
+    /// Lets every call waiting on the gate, and any future call,
+    /// through.
+    pub fn release(&self) {
+        let wakers = {
+            let mut state = self.inner.state.lock().unwrap();
+            state.released = true;
+            std::mem::take(&mut state.wakers)
+        };
+        self.inner.changed.notify_all();
+        wakers.into_iter().for_each(Waker::wake);
+    }
+
+    /// Whether the gate has been released.
+    pub fn is_released(&self) -> bool {
+        self.inner.state.lock().unwrap().released
+    }
+
+    /// Enters the gate and blocks until it is released
+    pub(crate) fn pass(&self) {
+        let state = self.enter();
+        let _state = self
+            .inner
+            .changed
+            .wait_while(state, |state| !state.released)
+            .unwrap();
+    }
+
+    /// Enters the gate and waits until it is released without
+    /// blocking
+    pub(crate) fn pass_async(&self) -> Pass<'_> {
+        Pass {
+            gate: self,
+            entered: false,
+        }
+    }
+
+    fn enter(&self) -> std::sync::MutexGuard<'_, State> {
+        let mut state = self.inner.state.lock().unwrap();
+        state.entered += 1;
+        self.inner.changed.notify_all();
+        state
+    }
+}
+
+impl fmt::Debug for CallGate {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        let state = self.inner.state.lock().unwrap();
+        f.debug_struct("CallGate")
+            .field("entered", &state.entered)
+            .field("released", &state.released)
+            .finish()
+    }
+}
+
+/// Future returned by [`CallGate::pass_async`]
+pub(crate) struct Pass<'g> {
+    gate: &'g CallGate,
+    entered: bool,
+}
+
+impl Future for Pass<'_> {
+    type Output = ();
+
+    fn poll(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<()> {
+        let mut state = if self.entered {
+            self.gate.inner.state.lock().unwrap()
+        } else {
+            self.entered = true;
+            self.gate.enter()
+        };
+
+        if state.released {
+            return Poll::Ready(());
+        }
+
+        if !state.wakers.iter().any(|w| w.will_wake(cx.waker())) {
+            state.wakers.push(cx.waker().clone());
+        }
+        Poll::Pending
+    }
+}
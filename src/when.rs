@@ -1,17 +1,27 @@
 //! Tools to stub the implementation or return value of your mocks.
 
+mod local;
 mod once;
+mod scoped;
+mod times;
+mod yield_times;
 
-use std::num::NonZeroUsize;
+use std::{borrow::Borrow, future::Future, marker::PhantomData, panic::Location, sync::Arc};
 
 use crate::{
     matcher::{AnyInvocation, InvocationMatcher},
-    mock::{self, stub},
-    Faux,
+    mock::{self, stub, MethodId},
+    scope::Scopable,
+    sync::CallGate,
+    Faux, Scope,
 };
 
+use local::Local;
 pub use once::Once;
+pub use scoped::Scoped;
 use stub::Stub;
+pub use times::Times;
+use yield_times::YieldTimes;
 
 /// Provides methods to stub the implementation or return value of the
 /// stubbed method.
@@ -25,51 +35,110 @@ use stub::Stub;
 /// By default, all methods are stubbed indefinitely. Thus, any
 /// stubbed values needs to be cloneable and any stubbed
 /// implementation cannot consume variables. Use the [`times`] and
-/// [`once`] methods to override these defaults.
+/// [`once`] methods to override these defaults, or [`never`] to
+/// assert that a method is not called.
 ///
 /// Do *NOT* rely on the signature of `When`. While changing the
 /// methods of `When` will be considered a breaking change, changing
 /// the generics within `When` will not.
 ///
 /// [`when!`]: crate::when!
+/// [`never`]: When::never
 /// [`once`]: When::once
 /// [`times`]: When::times
 /// [`with_args`]: When::with_args
-pub struct When<'m, R, I, O, M: InvocationMatcher<I>> {
+pub struct When<'m, R, I, O, M: InvocationMatcher<I>, S = ()> {
     // Set at creation and immutable. Could be replaced with just `&'m
     // mut Mock<'static, I,O>` but that makes `When` no longer be
     // contravariat on `I` which makes some valid code not compile.
     id: fn(R, I) -> O,
-    name: &'static str,
+    method: MethodId,
     store: &'m mut mock::Store<'static>,
+    // where the stub is being created, for error messages
+    location: &'static Location<'static>,
     // defaulted at creation but mutable
-    times: Option<stub::Times>,
+    times: Times,
+    gate: Option<CallGate>,
+    /// Depth of the scope the stub is created in, 0 if unscoped
+    scope: usize,
+    /// Whether the output of the method may only borrow from its
+    /// receiver
+    borrows_receiver: bool,
+    /// `fn(I)` with the arguments as written in the method if the
+    /// stubs may borrow from a scope, `()` otherwise. Invariant so it
+    /// cannot be coerced into another witness
+    scopable: PhantomData<fn(S) -> S>,
     matcher: M,
 }
 
 impl<'m, R, I, O> When<'m, R, I, O, AnyInvocation> {
     #[doc(hidden)]
-    pub fn new(id: fn(R, I) -> O, name: &'static str, faux: &'m mut Faux) -> Self {
+    #[track_caller]
+    pub fn new(
+        id: fn(R, I) -> O,
+        method: MethodId,
+        borrows_receiver: bool,
+        faux: &'m mut Faux,
+    ) -> Self {
+        Self::create(id, method, borrows_receiver, faux)
+    }
+}
+
+impl<'m, R, I, O, S> When<'m, R, I, O, AnyInvocation, S> {
+    /// Creates a `When` that may be stubbed in a scope.
+    ///
+    /// # Safety
+    ///
+    /// `S` must be `fn(I)` with the types of the arguments written as
+    /// in the method, so that any lifetime elided from them is
+    /// higher-ranked. Neither the arguments nor the output may use
+    /// the lifetimes or the generic types of the method, except for
+    /// an output that only borrows from the receiver.
+    #[doc(hidden)]
+    #[track_caller]
+    pub unsafe fn new_scopable(
+        id: fn(R, I) -> O,
+        method: MethodId,
+        borrows_receiver: bool,
+        faux: &'m mut Faux,
+    ) -> Self {
+        Self::create(id, method, borrows_receiver, faux)
+    }
+
+    #[track_caller]
+    fn create(
+        id: fn(R, I) -> O,
+        method: MethodId,
+        borrows_receiver: bool,
+        faux: &'m mut Faux,
+    ) -> Self {
         let store = faux.unique_store().expect("faux: failed to get unique handle to mock. Adding stubs to a mock instance may only be done prior to cloning the mock.");
 
         When {
             id,
-            name,
+            method,
             store,
+            location: Location::caller(),
             matcher: AnyInvocation,
-            times: Some(stub::Times::Always),
+            times: Times::ALWAYS,
+            gate: None,
+            scope: 0,
+            borrows_receiver,
+            scopable: PhantomData,
         }
     }
 }
 
-impl<'m, R, I, O, M: InvocationMatcher<I> + Send + 'static> When<'m, R, I, O, M> {
+impl<'m, R, I, O, M: InvocationMatcher<I> + Send + Sync + 'static, S> When<'m, R, I, O, M, S> {
     /// Sets the return value of the stubbed method.
     ///
     /// Requires the value to be static. For a more lax but unsafe
     /// alternative, use [`then_unchecked_return`].
     ///
     /// The returned value will be cloned on each invocation of the
-    /// stub. Using this method to return another mock instance will
+    /// stub. Concurrent invocations clone it at the same time, so it
+    /// must be `Sync`; use [`then`] to return values that are
+    /// not. Using this method to return another mock instance will
     /// fail unless that stub instance has its clone method already
     /// stubbed.
     ///
@@ -97,12 +166,13 @@ impl<'m, R, I, O, M: InvocationMatcher<I> + Send + 'static> When<'m, R, I, O, M>
     /// }
     /// ```
     ///
+    /// [`then`]: When::then
     /// [`then_unchecked_return`]: When::then_unchecked_return
     pub fn then_return(self, value: O)
     where
-        O: Send + Clone + 'static,
+        O: Send + Sync + Clone + 'static,
     {
-        self.then(move |_: I| value.clone());
+        self.then_shared(move |_: I| value.clone());
     }
 
     /// Sets the implementation of the stubbed method to the provided
@@ -173,6 +243,141 @@ impl<'m, R, I, O, M: InvocationMatcher<I> + Send + 'static> When<'m, R, I, O, M>
         self.add_stub(Box::new(stub));
     }
 
+    /// Analog of [`then`] for closures that do not mutate their
+    /// captures.
+    ///
+    /// Invocations of a stub set with [`then`] run one at a time,
+    /// since the closure is `FnMut`. The closure given here is
+    /// shared, so concurrent invocations run it at the same time.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use std::{sync::Arc, thread};
+    ///
+    /// #[faux::create]
+    /// pub struct Foo {}
+    ///
+    /// #[faux::methods]
+    /// impl Foo {
+    ///     pub fn double(&self, a: u32) -> u32 {
+    ///       /* implementation code */
+    ///       # panic!()
+    ///     }
+    /// }
+    ///
+    /// fn main() {
+    ///   let mut mock = Foo::faux();
+    ///   faux::when!(mock.double).then_shared(|a| a * 2);
+    ///
+    ///   let mock = Arc::new(mock);
+    ///   let handles: Vec<_> = (0..4)
+    ///       .map(|a| {
+    ///           let mock = mock.clone();
+    ///           thread::spawn(move || mock.double(a))
+    ///       })
+    ///       .collect();
+    ///   let doubled: Vec<_> = handles.into_iter().map(|h| h.join().unwrap()).collect();
+    ///   assert_eq!(doubled, vec![0, 2, 4, 6]);
+    /// }
+    /// ```
+    ///
+    /// [`then`]: When::then
+    pub fn then_shared(self, stub: impl Fn(I) -> O + 'static + Send + Sync)
+    where
+        O: 'static,
+    {
+        self.add_answer(stub::Answer::Shared(Arc::new(stub)));
+    }
+
+    /// Analog of [`then_return`] for values that are not `Send`,
+    /// such as an `Rc`.
+    ///
+    /// # Panics
+    ///
+    /// The stubbed method panics if it is called from another thread
+    /// than the one that created the stub. If the mock is dropped on
+    /// another thread, the value is leaked instead of dropped.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use std::rc::Rc;
+    ///
+    /// #[faux::create]
+    /// pub struct Foo {}
+    ///
+    /// #[faux::methods]
+    /// impl Foo {
+    ///     pub fn name(&self) -> Rc<str> {
+    ///       /* implementation code */
+    ///       # panic!()
+    ///     }
+    /// }
+    ///
+    /// fn main() {
+    ///   let mut mock = Foo::faux();
+    ///
+    ///   let name: Rc<str> = Rc::from("faux");
+    ///   faux::when!(mock.name).then_return_local(name.clone());
+    ///   assert_eq!(mock.name(), name);
+    /// }
+    /// ```
+    ///
+    /// [`then_return`]: When::then_return
+    pub fn then_return_local(self, value: O)
+    where
+        O: Clone + 'static,
+    {
+        self.then_local(move |_: I| value.clone());
+    }
+
+    /// Analog of [`then`] for closures that are not `Send`, such as
+    /// closures that capture an `Rc<RefCell<_>>`.
+    ///
+    /// # Panics
+    ///
+    /// The stubbed method panics if it is called from another thread
+    /// than the one that created the stub. If the mock is dropped on
+    /// another thread, the closure is leaked instead of dropped.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use std::{cell::RefCell, rc::Rc};
+    ///
+    /// #[faux::create]
+    /// pub struct Foo {}
+    ///
+    /// #[faux::methods]
+    /// impl Foo {
+    ///     pub fn log(&self, message: String) {
+    ///       /* implementation code */
+    ///       # panic!()
+    ///     }
+    /// }
+    ///
+    /// fn main() {
+    ///   let mut mock = Foo::faux();
+    ///
+    ///   let logged = Rc::new(RefCell::new(vec![]));
+    ///   let log = logged.clone();
+    ///   faux::when!(mock.log).then_local(move |message| log.borrow_mut().push(message));
+    ///
+    ///   mock.log("hello".to_string());
+    ///   assert_eq!(*logged.borrow(), vec!["hello".to_string()]);
+    /// }
+    /// ```
+    ///
+    /// [`then`]: When::then
+    pub fn then_local(self, stub: impl FnMut(I) -> O + 'static)
+    where
+        O: 'static,
+    {
+        let mut stub = Local::new(stub);
+        self.then(move |input| (stub.get_mut())(input));
+    }
+
     /// Analog of [`then_return`] that allows stubbing non-static
     /// return values.
     ///
@@ -330,9 +535,131 @@ impl<'m, R, I, O, M: InvocationMatcher<I> + Send + 'static> When<'m, R, I, O, M>
         self.add_stub(stub);
     }
 
-    /// Limits the number of calls for which a mock is active.
+    /// Sets the implementation of a stubbed `async` method to the
+    /// provided closure returning a future.
+    ///
+    /// The future is awaited by the mocked method, so it may be
+    /// pending for as long as it needs. Use it to test timeouts,
+    /// cancellation, or `select!`s over mocked methods. The future
+    /// cannot borrow from the inputs.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// #[faux::create]
+    /// pub struct Foo {}
+    ///
+    /// #[faux::methods]
+    /// impl Foo {
+    ///     pub async fn fetch(&self, id: u32) -> String {
+    ///       /* implementation code */
+    ///       # panic!()
+    ///     }
+    /// }
+    ///
+    /// fn main() {
+    ///   let mut mock = Foo::faux();
+    ///
+    ///   faux::when!(mock.fetch).then_async(|id| async move { format!("item {}", id) });
+    ///   # let fetched = futures::executor::block_on(mock.fetch(3));
+    ///   # assert_eq!(fetched, "item 3");
+    /// }
+    /// ```
+    ///
+    /// # Panics
+    ///
+    /// Non-`async` methods cannot await the future, so calling them
+    /// with a stub set by this method panics.
+    pub fn then_async<F>(self, mut stub: impl FnMut(I) -> F + 'static + Send)
+    where
+        F: Future<Output = O> + Send + 'static,
+        O: 'static,
+    {
+        self.add_async_stub(Box::new(
+            move |input: I| -> stub::PendingOutput<'static, O> { Box::pin(stub(input)) },
+        ));
+    }
+
+    /// Makes a stubbed `async` method never resolve.
+    ///
+    /// Useful to test timeouts or cancellation of code awaiting the
+    /// method. See [`then_async`](When::then_async).
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use futures::FutureExt as _;
+    ///
+    /// #[faux::create]
+    /// pub struct Foo {}
+    ///
+    /// #[faux::methods]
+    /// impl Foo {
+    ///     pub async fn fetch(&self) -> u32 {
+    ///       /* implementation code */
+    ///       # panic!()
+    ///     }
+    /// }
+    ///
+    /// fn main() {
+    ///   let mut mock = Foo::faux();
+    ///
+    ///   faux::when!(mock.fetch).then_pending();
+    ///   assert_eq!(mock.fetch().now_or_never(), None);
+    /// }
+    /// ```
+    pub fn then_pending(self)
+    where
+        O: Send + 'static,
+    {
+        self.then_async(|_: I| std::future::pending());
+    }
+
+    /// Makes a stubbed `async` method be pending for `times` polls
+    /// before resolving to `value`.
+    ///
+    /// The method wakes itself up each time it is pending. Useful to
+    /// test code that races the method against other futures. See
+    /// [`then_async`](When::then_async).
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use futures::FutureExt as _;
+    ///
+    /// #[faux::create]
+    /// pub struct Foo {}
+    ///
+    /// #[faux::methods]
+    /// impl Foo {
+    ///     pub async fn fetch(&self) -> u32 {
+    ///       /* implementation code */
+    ///       # panic!()
+    ///     }
+    /// }
+    ///
+    /// fn main() {
+    ///   let mut mock = Foo::faux();
+    ///
+    ///   faux::when!(mock.fetch).then_yield_times(2, 10);
+    ///   // not ready on its first poll
+    ///   assert_eq!(mock.fetch().now_or_never(), None);
+    ///   // but resolves when awaited
+    ///   assert_eq!(futures::executor::block_on(mock.fetch()), 10);
+    /// }
+    /// ```
+    pub fn then_yield_times(self, times: usize, value: O)
+    where
+        O: Send + Clone + 'static,
+    {
+        self.then_async(move |_: I| YieldTimes::new(times, value.clone()));
+    }
+
+    /// Sets how many times the mock may be called.
     ///
-    /// Calls past the limit will result in a panic.
+    /// A number limits the calls for which the mock is active but
+    /// does not require any calls. A range also sets a minimum number
+    /// of calls, which is checked once the mock is dropped.
     ///
     /// # Examples
     ///
@@ -363,6 +690,30 @@ impl<'m, R, I, O, M: InvocationMatcher<I> + Send + 'static> When<'m, R, I, O, M>
     /// }
     /// ```
     ///
+    /// ```rust
+    /// # #[faux::create]
+    /// # pub struct Foo {}
+    /// #
+    /// # #[faux::methods]
+    /// # impl Foo {
+    /// #     pub fn single_arg(&self, a: u8) -> Vec<i8> {
+    /// #       panic!()
+    /// #     }
+    /// # }
+    /// #
+    /// fn main() {
+    ///   let mut mock = Foo::faux();
+    ///
+    ///   // requires between 2 and 5 calls
+    ///   faux::when!(mock.single_arg)
+    ///       .times(2..=5)
+    ///       .then(|input| vec![input as i8]);
+    ///
+    ///   assert_eq!(mock.single_arg(8), vec![8]);
+    ///   assert_eq!(mock.single_arg(8), vec![8]);
+    /// }
+    /// ```
+    ///
     /// ## Panics
     ///
     /// Panics if the mock is called more times than specified.
@@ -393,11 +744,105 @@ impl<'m, R, I, O, M: InvocationMatcher<I> + Send + 'static> When<'m, R, I, O, M>
     ///   }
     /// }
     /// ```
-    pub fn times(mut self, times: usize) -> Self {
-        self.times = NonZeroUsize::new(times).map(stub::Times::Times);
+    ///
+    /// Panics when the mock is dropped if it was called fewer times
+    /// than the range requires. All unmet expectations of the mock
+    /// are reported at once. Nothing is checked if the thread is
+    /// already panicking.
+    ///
+    /// ```rust should_panic
+    /// # #[faux::create]
+    /// # pub struct Foo {}
+    /// #
+    /// # #[faux::methods]
+    /// # impl Foo {
+    /// #     pub fn single_arg(&self, a: u8) -> Vec<i8> {
+    /// #       panic!()
+    /// #     }
+    /// # }
+    /// #
+    /// fn main() {
+    ///   let mut mock = Foo::faux();
+    ///
+    ///   faux::when!(mock.single_arg)
+    ///       .times(2..=5)
+    ///       .then(|input| vec![input as i8]);
+    ///
+    ///   mock.single_arg(8);
+    ///   // panics: expected between 2 and 5 calls but was called 1 time
+    /// }
+    /// ```
+    ///
+    /// Panics right away if the range is empty, e.g. `5..3` or `..0`.
+    pub fn times(mut self, times: impl Into<Times>) -> Self {
+        self.times = times.into();
         self
     }
 
+    /// Limits the mock to at most `times` calls.
+    ///
+    /// Same as [`times`](When::times) with a number.
+    pub fn at_most(self, times: usize) -> Self {
+        self.times(Times::at_most(times))
+    }
+
+    /// Requires the mock to be called at least `times` times.
+    ///
+    /// Checked once the mock is dropped. See [`times`](When::times).
+    pub fn at_least(self, times: usize) -> Self {
+        self.times(Times::at_least(times))
+    }
+
+    /// Requires the mock to be called between `min` and `max` times,
+    /// both inclusive.
+    ///
+    /// Same as [`times`](When::times) with `min..=max`, so it panics
+    /// if `min` is greater than `max`.
+    #[track_caller]
+    pub fn between(self, min: usize, max: usize) -> Self {
+        self.times(Times::from(min..=max))
+    }
+
+    /// Expects the mock to never be called.
+    ///
+    /// Calls that match this stub panic, explaining that the method
+    /// was called but expected never. Use [`when!`](crate::when!)
+    /// argument matchers to only forbid certain calls.
+    ///
+    /// # Examples
+    ///
+    /// ```rust should_panic
+    /// #[faux::create]
+    /// pub struct Foo {}
+    ///
+    /// #[faux::methods]
+    /// impl Foo {
+    ///     pub fn single_arg(&self, a: u8) -> Vec<i8> {
+    ///       /* implementation code */
+    ///       # panic!()
+    ///     }
+    /// }
+    ///
+    /// fn main() {
+    ///   let mut mock = Foo::faux();
+    ///
+    ///   faux::when!(mock.single_arg).then(|input| vec![input as i8]);
+    ///   faux::when!(mock.single_arg(0)).never();
+    ///
+    ///   assert_eq!(mock.single_arg(8), vec![8]);
+    ///   // panics: `Foo::single_arg` was called but expected never
+    ///   mock.single_arg(0);
+    /// }
+    /// ```
+    pub fn never(self) {
+        self.store.get_mut(self.id, self.method).add_stub(Stub::new(
+            stub::Answer::Exhausted,
+            Times::NEVER,
+            self.matcher,
+            self.location,
+        ));
+    }
+
     /// Limits mock to one call, allowing mocks to consume captured variables.
     ///
     /// Panics if the mock is called more than once.
@@ -453,7 +898,133 @@ impl<'m, R, I, O, M: InvocationMatcher<I> + Send + 'static> When<'m, R, I, O, M>
     /// }
     /// ```
     pub fn once(self) -> Once<'m, R, I, O, M> {
-        Once::new(self.id, self.name, self.store, self.matcher)
+        Once::new(
+            self.id,
+            self.method,
+            self.store,
+            self.location,
+            self.gate,
+            self.matcher,
+        )
+    }
+
+    /// Creates the stub in `scope`, letting it borrow from the test.
+    ///
+    /// The stub is removed from the mock once the scope closes. See
+    /// [`faux::scope`](crate::scope) for an example.
+    ///
+    /// Only methods whose stubs cannot hand out borrowed data past the
+    /// scope may be stubbed in one; for any other method this does
+    /// not compile. Their arguments may not borrow, including through
+    /// a lifetime elided from a path such as `Cow<str>`. Their output
+    /// may only borrow from the receiver, `'static` data, or the
+    /// lifetimes of the impl block. Neither may use the generic types
+    /// of the method.
+    ///
+    /// ```compile_fail
+    /// #[faux::create]
+    /// pub struct Config {}
+    ///
+    /// #[faux::methods]
+    /// impl Config {
+    ///     pub fn get(&self, _key: &str) -> &str {
+    ///       /* implementation code */
+    ///       # panic!()
+    ///     }
+    /// }
+    ///
+    /// # fn main() {
+    /// let mut config = Config::faux();
+    /// faux::scope(&mut config, |s, config| {
+    ///     // error: the key could be returned after it was dropped
+    ///     faux::when!(config.get).in_scope(s).then(|key| key);
+    /// });
+    /// # }
+    /// ```
+    ///
+    /// ```compile_fail
+    /// # #[faux::create]
+    /// # pub struct Config {}
+    /// #[faux::methods]
+    /// impl Config {
+    ///     pub fn pick<'a>(&self) -> &'a str {
+    ///       /* implementation code */
+    ///       # panic!()
+    ///     }
+    /// }
+    ///
+    /// # fn main() {
+    /// # let mut config = Config::faux();
+    /// let value = String::from("scoped");
+    /// faux::scope(&mut config, |s, config| {
+    ///     // error: each call picks its own lifetime for the output
+    ///     faux::when!(config.pick).in_scope(s).then(|_| &value);
+    /// });
+    /// # }
+    /// ```
+    ///
+    /// ```compile_fail
+    /// # #[faux::create]
+    /// # pub struct Config {}
+    /// #[faux::methods]
+    /// impl Config {
+    ///     pub fn parse<T: std::str::FromStr>(&self) -> Option<T> {
+    ///       /* implementation code */
+    ///       # panic!()
+    ///     }
+    /// }
+    ///
+    /// # fn main() {
+    /// # let mut config = Config::faux();
+    /// faux::scope(&mut config, |s, config| {
+    ///     // error: the method is generic
+    ///     faux::when!(config.parse::<u32>()).in_scope(s).then(|_| Some(3));
+    /// });
+    /// # }
+    /// ```
+    ///
+    /// # Panics
+    ///
+    /// Panics if `scope` was opened on another mock.
+    #[track_caller]
+    pub fn in_scope<'env>(self, scope: &Scope<'env>) -> Scoped<'m, 'env, R, I, O, M>
+    where
+        S: Scopable<I>,
+    {
+        assert!(
+            scope.is_of(self.store),
+            "faux: `{}::{}` cannot be stubbed in the scope of another mock",
+            self.store.struct_name,
+            self.method.name(),
+        );
+        Scoped::new(When {
+            id: self.id,
+            method: self.method,
+            store: self.store,
+            location: self.location,
+            times: self.times,
+            gate: self.gate,
+            scope: scope.depth(),
+            borrows_receiver: self.borrows_receiver,
+            scopable: PhantomData,
+            matcher: self.matcher,
+        })
+    }
+
+    /// Holds calls to the stub until `gate` is released.
+    ///
+    /// Each call enters the gate once it is matched to the stub and
+    /// counted as one of its calls. The stub only answers it after the
+    /// gate is released, so the answer has not run while the call is
+    /// held. Blocking methods
+    /// block their thread while waiting; `async` methods stay
+    /// pending. See [`CallGate`] for an example.
+    ///
+    /// Set it before the answer, like [`times`](When::times), e.g.
+    /// `when!(mock.fetch).block_until(&gate).then_return(5)`.
+    pub fn block_until(mut self, gate: &CallGate) -> Self {
+        self.gate = Some(gate.clone());
+        self
     }
 
     /// Specifies a matcher for the invocation.
@@ -472,27 +1043,130 @@ impl<'m, R, I, O, M: InvocationMatcher<I> + Send + 'static> When<'m, R, I, O, M>
     ///
     /// For more complex cases, you may pass a custom
     /// [`InvocationMatcher`](InvocationMatcher).
-    pub fn with_args<N: InvocationMatcher<I> + Send + 'static>(
+    pub fn with_args<N: InvocationMatcher<I> + Send + Sync + 'static>(
         self,
         matcher: N,
-    ) -> When<'m, R, I, O, N> {
+    ) -> When<'m, R, I, O, N, S> {
         When {
             matcher,
             id: self.id,
-            name: self.name,
+            method: self.method,
             store: self.store,
+            location: self.location,
             times: self.times,
+            gate: self.gate,
+            scope: self.scope,
+            borrows_receiver: self.borrows_receiver,
+            scopable: PhantomData,
         }
     }
 
     fn add_stub(self, stub: Box<dyn FnMut(I) -> O + Send + 'static>) {
-        let answer = match self.times {
-            None => stub::Answer::Exhausted,
-            Some(times) => stub::Answer::Many { times, stub },
+        self.add_answer(stub::Answer::Many(stub));
+    }
+
+    fn add_async_stub(
+        self,
+        stub: Box<dyn FnMut(I) -> stub::PendingOutput<'static, O> + Send + 'static>,
+    ) {
+        self.add_answer(stub::Answer::Async(stub));
+    }
+
+    fn add_answer(self, answer: stub::Answer<'static, I, O>) {
+        let answer = match self.times.max {
+            Some(0) => stub::Answer::Exhausted,
+            _ => answer,
         };
 
-        self.store
-            .get_mut(self.id, self.name)
-            .add_stub(Stub::new(answer, self.matcher));
+        self.store.get_mut(self.id, self.method).add_stub(
+            Stub::new(answer, self.times, self.matcher, self.location)
+                .gated(self.gate)
+                .scoped(self.scope),
+        );
+    }
+}
+
+impl<'m, 'o, R, I, U, M, S> When<'m, R, I, &'o U, M, S>
+where
+    U: ?Sized + Sync + 'static,
+    M: InvocationMatcher<I> + Send + Sync + 'static,
+{
+    /// Sets the stubbed method, which returns a reference, to return
+    /// a reference to `value`.
+    ///
+    /// The value is kept alive by the mock for as long as the mock
+    /// exists, so the references can be tied to the receiver of the
+    /// method. Every invocation returns a reference to the same
+    /// value.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the returned reference may borrow from anything but
+    /// the receiver of the method, e.g. `fn get<'a>(&self, key: &'a
+    /// str) -> &'a str`, since it could outlive the mock.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// #[faux::create]
+    /// pub struct Foo {}
+    ///
+    /// #[derive(Debug, PartialEq)]
+    /// pub struct Config {
+    ///     retries: u32,
+    /// }
+    ///
+    /// #[faux::methods]
+    /// impl Foo {
+    ///     pub fn name(&self) -> &str {
+    ///       /* implementation code */
+    ///       # panic!()
+    ///     }
+    ///
+    ///     pub fn config(&self) -> &Config {
+    ///       /* implementation code */
+    ///       # panic!()
+    ///     }
+    /// }
+    ///
+    /// fn main() {
+    ///   let mut mock = Foo::faux();
+    ///
+    ///   faux::when!(mock.name).then_return_ref(String::from("foo"));
+    ///   faux::when!(mock.config).then_return_ref(Config { retries: 3 });
+    ///
+    ///   assert_eq!(mock.name(), "foo");
+    ///   assert_eq!(mock.config(), &Config { retries: 3 });
+    /// }
+    /// ```
+    pub fn then_return_ref<T>(self, value: T)
+    where
+        T: Borrow<U> + Send + Sync + 'static,
+    {
+        assert!(
+            self.borrows_receiver,
+            "faux: `{}::{}` may return references that outlive the mock. Only methods that return references borrowed from `self` may be stubbed with `then_return_ref`.",
+            self.store.struct_name,
+            self.method.name(),
+        );
+
+        let value = self.store.keep(value);
+        // Safety: the store keeps the value alive and never mutates it
+        let output = Kept(unsafe { (*value).borrow() });
+        self.add_answer(stub::Answer::Shared(Arc::new(move |_: I| output.get())));
+    }
+}
+
+/// Pointer to a value kept alive by the mock store
+struct Kept<U: ?Sized>(*const U);
+
+// Safety: only shared references to the value are handed out
+unsafe impl<U: ?Sized + Sync> Send for Kept<U> {}
+unsafe impl<U: ?Sized + Sync> Sync for Kept<U> {}
+
+impl<U: ?Sized> Kept<U> {
+    fn get<'o>(&self) -> &'o U {
+        // Safety: the store outlives every invocation of its stubs
+        unsafe { &*self.0 }
     }
 }
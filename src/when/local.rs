@@ -0,0 +1,63 @@
+use std::{
+    mem::ManuallyDrop,
+    thread::{self, ThreadId},
+};
+
+/// A value that may only be used on the thread that created it
+///
+/// Lets stubs capture values that are not `Send`. If it is dropped on
+/// another thread the value is leaked instead, since dropping it
+/// there would be just as unsound as using it.
+pub struct Local<T> {
+    value: ManuallyDrop<T>,
+    thread: ThreadId,
+}
+
+// Safety: the value is only ever accessed or dropped on the thread
+// that created it
+unsafe impl<T> Send for Local<T> {}
+
+impl<T> Local<T> {
+    pub fn new(value: T) -> Self {
+        Local {
+            value: ManuallyDrop::new(value),
+            thread: thread::current().id(),
+        }
+    }
+
+    /// # Panics
+    ///
+    /// Panics if called on another thread than the one that created
+    /// the value.
+    pub fn get_mut(&mut self) -> &mut T {
+        self.assert_thread();
+        &mut self.value
+    }
+
+    /// # Panics
+    ///
+    /// Panics if called on another thread than the one that created
+    /// the value.
+    pub fn into_inner(self) -> T {
+        self.assert_thread();
+        let mut local = ManuallyDrop::new(self);
+        // Safety: `local` is never dropped, so neither is its value
+        unsafe { ManuallyDrop::take(&mut local.value) }
+    }
+
+    fn assert_thread(&self) {
+        assert!(
+            self.thread == thread::current().id(),
+            "faux: a stub created with `then_local` or `then_return_local` may only be called on the thread that created it"
+        );
+    }
+}
+
+impl<T> Drop for Local<T> {
+    fn drop(&mut self) {
+        if self.thread == thread::current().id() {
+            // Safety: the value is not used after being dropped
+            unsafe { ManuallyDrop::drop(&mut self.value) }
+        }
+    }
+}
@@ -1,8 +1,13 @@
+use std::panic::Location;
+
 use crate::{
     matcher::InvocationMatcher,
-    mock::{self, stub, Stub},
+    mock::{self, stub, MethodId, Stub},
+    sync::CallGate,
 };
 
+use super::{Local, Times};
+
 /// Similar to [When](struct.When), but only stubs once.
 ///
 /// Stubbed values do not need to be cloneable. Stubbed
@@ -13,23 +18,29 @@ use crate::{
 /// the generics within `Once` will not.
 pub struct Once<'m, R, I, O, M: InvocationMatcher<I>> {
     id: fn(R, I) -> O,
-    name: &'static str,
+    method: MethodId,
     store: &'m mut mock::Store<'static>,
+    location: &'static Location<'static>,
+    gate: Option<CallGate>,
     matcher: M,
 }
 
-impl<'m, R, I, O, M: InvocationMatcher<I> + Send + 'static> Once<'m, R, I, O, M> {
+impl<'m, R, I, O, M: InvocationMatcher<I> + Send + Sync + 'static> Once<'m, R, I, O, M> {
     #[doc(hidden)]
     pub fn new(
         id: fn(R, I) -> O,
-        name: &'static str,
+        method: MethodId,
         store: &'m mut mock::Store<'static>,
+        location: &'static Location<'static>,
+        gate: Option<CallGate>,
         matcher: M,
     ) -> Self {
         Once {
             id,
-            name,
+            method,
             store,
+            location,
+            gate,
             matcher,
         }
     }
@@ -105,6 +116,39 @@ impl<'m, R, I, O, M: InvocationMatcher<I> + Send + 'static> Once<'m, R, I, O, M>
         self.add_stub(Box::new(stub))
     }
 
+    /// Analog of [When.then_return_local] where the value does not
+    /// need to be cloneable.
+    ///
+    /// [When.then_return_local]: struct.When.html#method.then_return_local
+    ///
+    /// # Panics
+    ///
+    /// The stubbed method panics if it is called from another thread
+    /// than the one that created the stub.
+    pub fn then_return_local(self, value: O)
+    where
+        O: 'static,
+    {
+        self.then_local(move |_: I| value)
+    }
+
+    /// Analog of [When.then_local] where the stub may consume
+    /// captured variables.
+    ///
+    /// [When.then_local]: struct.When.html#method.then_local
+    ///
+    /// # Panics
+    ///
+    /// The stubbed method panics if it is called from another thread
+    /// than the one that created the stub.
+    pub fn then_local(self, stub: impl FnOnce(I) -> O + 'static)
+    where
+        O: 'static,
+    {
+        let stub = Local::new(stub);
+        self.then(move |input| (stub.into_inner())(input))
+    }
+
     /// Analog of [When.then_unchecked_return] where the value does
     /// not need to be cloneable.
     ///
@@ -186,8 +230,14 @@ impl<'m, R, I, O, M: InvocationMatcher<I> + Send + 'static> Once<'m, R, I, O, M>
     }
 
     fn add_stub(self, stub: Box<dyn FnOnce(I) -> O + Send + 'static>) {
-        self.store
-            .get_mut(self.id, self.name)
-            .add_stub(Stub::new(stub::Answer::Once(stub), self.matcher));
+        self.store.get_mut(self.id, self.method).add_stub(
+            Stub::new(
+                stub::Answer::Once(stub),
+                Times::from(1),
+                self.matcher,
+                self.location,
+            )
+            .gated(self.gate),
+        );
     }
 }
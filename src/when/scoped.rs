@@ -0,0 +1,50 @@
+use std::marker::PhantomData;
+
+use super::When;
+use crate::matcher::InvocationMatcher;
+
+/// Provides methods to stub the implementation or return value of
+/// the mocked method for the duration of a [`Scope`].
+///
+/// Created using [`When::in_scope`]. Unlike [`When`], the stubs may
+/// borrow anything that outlives the scope.
+///
+/// [`Scope`]: crate::Scope
+pub struct Scoped<'m, 'env, R, I, O, M: InvocationMatcher<I>> {
+    when: When<'m, R, I, O, M>,
+    // invariant like the scope itself
+    _env: PhantomData<&'env mut &'env ()>,
+}
+
+impl<'m, 'env, R, I, O, M: InvocationMatcher<I> + Send + Sync + 'static>
+    Scoped<'m, 'env, R, I, O, M>
+{
+    pub(super) fn new(when: When<'m, R, I, O, M>) -> Self {
+        Scoped {
+            when,
+            _env: PhantomData,
+        }
+    }
+
+    /// Analog of [`When::then_return`] where the value only needs to
+    /// outlive the scope.
+    pub fn then_return(self, value: O)
+    where
+        O: Send + Clone + 'env,
+    {
+        self.then(move |_: I| value.clone());
+    }
+
+    /// Analog of [`When::then`] where the closure only needs to
+    /// outlive the scope.
+    ///
+    /// The closure may return references to data it borrows, even if
+    /// the method returns a reference.
+    pub fn then(self, stub: impl FnMut(I) -> O + Send + 'env) {
+        let stub: Box<dyn FnMut(I) -> O + Send + 'env> = Box::new(stub);
+        // Safety: the stub is removed from the mock when the scope
+        // closes, before anything it borrows may be dropped
+        let stub: Box<_> = unsafe { std::mem::transmute(stub) };
+        self.when.add_stub(stub);
+    }
+}
@@ -0,0 +1,136 @@
+use std::{
+    fmt::{self, Formatter},
+    ops::{Range, RangeFrom, RangeFull, RangeInclusive, RangeTo, RangeToInclusive},
+};
+
+/// How many times a stub may be invoked.
+///
+/// Created from a number or a range of numbers. See
+/// [`When::times`](crate::When::times) for how each is interpreted.
+///
+/// # Panics
+///
+/// Panics if the range is empty, e.g. `5..3` or `..0`, since no
+/// number of calls would satisfy it.
+///
+/// # Examples
+///
+/// ```
+/// use faux::when::Times;
+///
+/// // at most 5 calls
+/// let at_most_five = Times::from(5);
+/// // between 2 and 5 calls
+/// let two_to_five = Times::from(2..=5);
+/// // at least 1 call
+/// let at_least_one = Times::from(1..);
+/// ```
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Times {
+    pub(crate) min: usize,
+    pub(crate) max: Option<usize>,
+}
+
+impl Times {
+    pub(crate) const ALWAYS: Times = Times { min: 0, max: None };
+    pub(crate) const NEVER: Times = Times {
+        min: 0,
+        max: Some(0),
+    };
+
+    pub(crate) fn at_least(min: usize) -> Self {
+        Times { min, max: None }
+    }
+
+    pub(crate) fn at_most(max: usize) -> Self {
+        Times {
+            min: 0,
+            max: Some(max),
+        }
+    }
+}
+
+impl fmt::Display for Times {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        match (self.min, self.max) {
+            (0, None) => f.write_str("any number of calls"),
+            (0, Some(max)) => write!(f, "at most {} {}", max, calls(max)),
+            (min, None) => write!(f, "at least {} {}", min, calls(min)),
+            (min, Some(max)) if min == max => write!(f, "exactly {} {}", min, calls(min)),
+            (min, Some(max)) => write!(f, "between {} and {} calls", min, max),
+        }
+    }
+}
+
+fn calls(count: usize) -> &'static str {
+    if count == 1 {
+        "call"
+    } else {
+        "calls"
+    }
+}
+
+#[track_caller]
+fn assert_not_empty(is_empty: bool, range: &dyn fmt::Debug) {
+    assert!(
+        !is_empty,
+        "faux: `{:?}` is an empty range, no number of calls is within it",
+        range
+    );
+}
+
+/// A number only limits the calls: `times(5)` allows up to five
+/// calls but does not require any.
+impl From<usize> for Times {
+    fn from(max: usize) -> Self {
+        Times::at_most(max)
+    }
+}
+
+impl From<Range<usize>> for Times {
+    #[track_caller]
+    fn from(range: Range<usize>) -> Self {
+        assert_not_empty(range.is_empty(), &range);
+        Times {
+            min: range.start,
+            max: Some(range.end - 1),
+        }
+    }
+}
+
+impl From<RangeInclusive<usize>> for Times {
+    #[track_caller]
+    fn from(range: RangeInclusive<usize>) -> Self {
+        assert_not_empty(range.is_empty(), &range);
+        Times {
+            min: *range.start(),
+            max: Some(*range.end()),
+        }
+    }
+}
+
+impl From<RangeFrom<usize>> for Times {
+    fn from(range: RangeFrom<usize>) -> Self {
+        Times::at_least(range.start)
+    }
+}
+
+impl From<RangeTo<usize>> for Times {
+    #[track_caller]
+    fn from(range: RangeTo<usize>) -> Self {
+        assert_not_empty(range.end == 0, &range);
+        Times::at_most(range.end - 1)
+    }
+}
+
+impl From<RangeToInclusive<usize>> for Times {
+    fn from(range: RangeToInclusive<usize>) -> Self {
+        Times::at_most(range.end)
+    }
+}
+
+impl From<RangeFull> for Times {
+    fn from(_: RangeFull) -> Self {
+        Times::ALWAYS
+    }
+}
@@ -0,0 +1,44 @@
+use std::{
+    future::Future,
+    pin::Pin,
+    task::{Context, Poll},
+};
+
+/// A future that is pending for a number of polls before resolving
+///
+/// It wakes itself up every time it is pending, so executors keep
+/// polling it without anything else happening.
+pub struct YieldTimes<O> {
+    remaining: usize,
+    value: Option<O>,
+}
+
+impl<O> YieldTimes<O> {
+    pub fn new(times: usize, value: O) -> Self {
+        YieldTimes {
+            remaining: times,
+            value: Some(value),
+        }
+    }
+}
+
+// the value is never pinned
+impl<O> Unpin for YieldTimes<O> {}
+
+impl<O> Future for YieldTimes<O> {
+    type Output = O;
+
+    fn poll(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<O> {
+        if self.remaining == 0 {
+            let value = self
+                .value
+                .take()
+                .expect("faux: future polled after completion");
+            return Poll::Ready(value);
+        }
+
+        self.remaining -= 1;
+        cx.waker().wake_by_ref();
+        Poll::Pending
+    }
+}
@@ -0,0 +1,164 @@
+use std::thread;
+
+#[faux::create]
+pub struct Connection {
+    addr: String,
+}
+
+#[faux::methods]
+impl Connection {
+    pub fn connect(addr: &str) -> Result<Self, String> {
+        Ok(Connection {
+            addr: addr.to_string(),
+        })
+    }
+
+    pub fn default_port() -> u16 {
+        80
+    }
+
+    pub async fn resolve(host: &str) -> String {
+        format!("{}:80", host)
+    }
+
+    pub fn addr(&self) -> String {
+        self.addr.clone()
+    }
+}
+
+fn greet(addr: &str) -> String {
+    match Connection::connect(addr) {
+        Ok(connection) => format!("hello {}", connection.addr()),
+        Err(e) => format!("failed: {}", e),
+    }
+}
+
+#[test]
+fn real_when_not_stubbed() {
+    assert_eq!(greet("localhost"), "hello localhost");
+    assert_eq!(Connection::default_port(), 80);
+}
+
+#[test]
+fn stubs_constructor() {
+    let mut connection = Connection::faux();
+    faux::when!(connection.addr).then_return("mocked".to_string());
+    faux::when!(Connection::connect)
+        .once()
+        .then_return(Ok(connection));
+
+    assert_eq!(greet("localhost"), "hello mocked");
+}
+
+#[test]
+fn stubs_with_arguments() {
+    faux::when!(Connection::connect(_)).then(|addr| Err(format!("{} is down", addr)));
+    faux::when!(Connection::connect("localhost")).then(|_| Err("refused".to_string()));
+
+    assert_eq!(greet("localhost"), "failed: refused");
+    assert_eq!(greet("remote"), "failed: remote is down");
+}
+
+#[test]
+fn stubs_without_arguments() {
+    faux::when!(Connection::default_port).then_return(8080);
+
+    assert_eq!(Connection::default_port(), 8080);
+}
+
+#[test]
+fn stubs_async() {
+    faux::when!(Connection::resolve).then(|host| format!("{}:8080", host));
+
+    let resolved = futures::executor::block_on(Connection::resolve("localhost"));
+    assert_eq!(resolved, "localhost:8080");
+}
+
+#[test]
+fn stubs_are_thread_local() {
+    faux::when!(Connection::default_port).then_return(8080);
+
+    let port = thread::spawn(Connection::default_port).join().unwrap();
+    assert_eq!(port, 80);
+}
+
+/// Registers stubs for the associated functions of `T`, through the
+/// hidden API the macros use
+fn stub_associated<T>(_: &T, method: faux::MethodId) {
+    let mut stubs = faux::Associated::new();
+    faux::When::new(
+        |_: (), _: ()| -> u32 { unreachable!() },
+        method,
+        false,
+        stubs.faux::<T>(),
+    )
+    .then_return(3);
+}
+
+fn is_stubbed<T>(_: &T, method: faux::MethodId) -> bool {
+    faux::Associated::stubbed::<T>(method).is_some()
+}
+
+#[test]
+fn types_with_the_same_name_have_their_own_stubs() {
+    // both closures are named `associated::..::{{closure}}`
+    let first = || 1;
+    let second = || 2;
+
+    let method = faux::MethodId::new::<()>("get");
+    stub_associated(&first, method);
+
+    assert!(is_stubbed(&first, method));
+    assert!(!is_stubbed(&second, method));
+}
+
+#[test]
+#[should_panic(expected = "`Connection::connect` had no suitable stubs")]
+fn stubbed_functions_need_matching_stubs() {
+    faux::when!(Connection::connect("localhost")).then(|_| Err("down".to_string()));
+
+    greet("remote");
+}
+
+#[test]
+fn unmet_stubs_do_not_abort_exiting_threads() {
+    thread::spawn(|| {
+        faux::when!(Connection::default_port)
+            .at_least(1)
+            .then_return(8080);
+    })
+    .join()
+    .unwrap();
+}
+
+#[test]
+fn cleared_stubs_call_the_real_implementation() {
+    faux::when!(Connection::default_port).then_return(8080);
+    assert_eq!(Connection::default_port(), 8080);
+
+    faux::clear_stubs();
+    assert_eq!(Connection::default_port(), 80);
+}
+
+#[test]
+#[should_panic(expected = "`default_port` expected at least 1 call but was never called")]
+fn clearing_checks_unmet_stubs() {
+    faux::when!(Connection::default_port)
+        .at_least(1)
+        .then_return(8080);
+
+    faux::clear_stubs();
+}
+
+#[test]
+fn guard_clears_stubs_once_dropped() {
+    {
+        let _stubs = faux::StubsGuard::new();
+        faux::when!(Connection::default_port)
+            .at_least(1)
+            .then_return(8080);
+        assert_eq!(Connection::default_port(), 8080);
+    }
+
+    assert_eq!(Connection::default_port(), 80);
+}
@@ -1,5 +1,10 @@
 #![allow(clippy::disallowed_names)]
 
+use std::task::Poll;
+
+use faux::sync::CallGate;
+use futures::FutureExt as _;
+
 #[faux::create]
 pub struct Foo {}
 
@@ -20,6 +25,10 @@ impl Foo {
     async fn private(&self) -> i32 {
         3
     }
+
+    pub fn sync(&self) -> i32 {
+        4
+    }
 }
 
 #[test]
@@ -39,3 +48,72 @@ fn mocked() {
     let fetched = futures::executor::block_on(foo.fetch());
     assert_eq!(fetched, 10);
 }
+
+#[test]
+fn then_async() {
+    let mut foo = Foo::faux();
+    let (sender, receiver) = futures::channel::oneshot::channel();
+    let mut receiver = Some(receiver);
+    faux::when!(foo.fetch)
+        .then_async(move |_| receiver.take().unwrap().map(|value| value.unwrap()));
+
+    futures::executor::block_on(async {
+        let mut fetched = foo.fetch().boxed();
+        assert!(futures::poll!(&mut fetched).is_pending());
+        sender.send(10).unwrap();
+        assert_eq!(fetched.await, 10);
+    });
+}
+
+#[test]
+fn then_pending() {
+    let mut foo = Foo::faux();
+    faux::when!(foo.fetch).then_pending();
+
+    let fetched = futures::executor::block_on(async {
+        futures::select! {
+            fetched = foo.fetch().fuse() => fetched,
+            other = futures::future::ready(7) => other,
+        }
+    });
+    assert_eq!(fetched, 7);
+}
+
+#[test]
+fn then_yield_times() {
+    let mut foo = Foo::faux();
+    faux::when!(foo.fetch).then_yield_times(2, 10);
+
+    futures::executor::block_on(async {
+        let mut fetched = foo.fetch().boxed();
+        assert!(futures::poll!(&mut fetched).is_pending());
+        assert!(futures::poll!(&mut fetched).is_pending());
+        assert_eq!(futures::poll!(&mut fetched), Poll::Ready(10));
+    });
+}
+
+#[test]
+#[should_panic(expected = "`Foo::sync` is not async but its stub answers asynchronously")]
+fn then_async_on_sync_method() {
+    let mut foo = Foo::faux();
+    faux::when!(foo.sync).then_async(|_| async { 5 });
+    foo.sync();
+}
+
+#[test]
+fn gate_holds_async_call_in_flight() {
+    let mut foo = Foo::faux();
+    let gate = CallGate::new();
+    faux::when!(foo.fetch).block_until(&gate).then_return(10);
+
+    futures::executor::block_on(async {
+        let mut fetched = foo.fetch().boxed();
+        assert!(futures::poll!(&mut fetched).is_pending());
+        assert_eq!(gate.entered(), 1);
+        assert!(futures::poll!(&mut fetched).is_pending());
+        assert_eq!(gate.entered(), 1);
+
+        gate.release();
+        assert_eq!(fetched.await, 10);
+    });
+}
@@ -0,0 +1,119 @@
+use std::{sync::mpsc, sync::Arc, thread, time::Duration};
+
+#[faux::create]
+pub struct Store {}
+
+#[faux::methods]
+impl Store {
+    pub fn flush(&self, _key: u32) {
+        unreachable!()
+    }
+
+    pub fn get(&self) -> u32 {
+        unreachable!()
+    }
+}
+
+#[test]
+fn waits_for_background_calls() {
+    let mut store = Store::faux();
+    faux::when!(store.flush).then_return(());
+
+    let store = Arc::new(store);
+    let background = {
+        let store = store.clone();
+        thread::spawn(move || {
+            for key in 0..3 {
+                thread::sleep(Duration::from_millis(10));
+                store.flush(key);
+            }
+        })
+    };
+
+    faux::await_calls!(store.flush(_), 3, Duration::from_secs(5));
+    background.join().unwrap();
+}
+
+#[test]
+fn counts_previous_calls() {
+    let mut store = Store::faux();
+    faux::when!(store.flush).then_return(());
+    store.flush(1);
+    store.flush(2);
+
+    faux::await_calls!(store.flush, 2, Duration::from_millis(1));
+}
+
+#[test]
+fn counts_matching_calls() {
+    let mut store = Store::faux();
+    faux::when!(store.flush).then_return(());
+    // not counted: made before waiting
+    store.flush(2);
+
+    let store = Arc::new(store);
+    let waiting = faux::await_calls!(store.flush(2), 2);
+
+    let (start, started) = mpsc::channel();
+    let background = {
+        let store = store.clone();
+        thread::spawn(move || {
+            started.recv().unwrap();
+            store.flush(1);
+            store.flush(2);
+            store.flush(1);
+            store.flush(2);
+        })
+    };
+
+    start.send(()).unwrap();
+    futures::executor::block_on(waiting);
+    background.join().unwrap();
+}
+
+#[test]
+#[should_panic(expected = "to be called 1 times; calls so far: 0")]
+fn matching_calls_are_counted_once_waiting() {
+    let mut store = Store::faux();
+    faux::when!(store.flush).then_return(());
+    store.flush(1);
+
+    faux::await_calls!(store.flush(1), 1, Duration::from_millis(1));
+}
+
+#[test]
+fn waits_asynchronously() {
+    let mut store = Store::faux();
+    faux::when!(store.get).then_return(3);
+
+    let store = Arc::new(store);
+    let waiting = faux::await_calls!(store.get(), 1);
+    let background = {
+        let store = store.clone();
+        thread::spawn(move || store.get())
+    };
+
+    futures::executor::block_on(waiting);
+    assert_eq!(background.join().unwrap(), 3);
+}
+
+#[test]
+#[should_panic(
+    expected = "faux: timed out after 10ms waiting for `Store::flush` to be called 2 times; calls so far: 1"
+)]
+fn times_out() {
+    let mut store = Store::faux();
+    faux::when!(store.flush).then_return(());
+    store.flush(1);
+
+    faux::await_calls!(store.flush(_), 2, Duration::from_millis(10));
+}
+
+#[test]
+#[should_panic(
+    expected = "faux: cannot wait for calls to `Store::get` because it was never stubbed"
+)]
+fn never_stubbed() {
+    let store = Store::faux();
+    faux::await_calls!(store.get(), 1, Duration::from_millis(10));
+}
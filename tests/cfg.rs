@@ -0,0 +1,83 @@
+mod mocked {
+    use std::rc::Rc;
+
+    #[faux::create(cfg = "test", self_type = "Rc")]
+    pub struct Counter {
+        count: u32,
+    }
+
+    #[faux::methods(self_type = "Rc", cfg = "test")]
+    impl Counter {
+        pub fn new(count: u32) -> Rc<Self> {
+            Rc::new(Counter { count })
+        }
+
+        pub fn count(&self) -> u32 {
+            self.count
+        }
+    }
+
+    #[test]
+    fn real_instance() {
+        let counter = Counter::new(3);
+        assert_eq!(counter.count(), 3);
+    }
+
+    #[test]
+    fn mocked() {
+        let mut counter = Counter::faux();
+        faux::when!(counter.count()).then_return(5);
+        assert_eq!(counter.count(), 5);
+    }
+}
+
+mod original {
+    #[faux::create(cfg = "not(test)")]
+    pub struct Counter {
+        count: u32,
+    }
+
+    #[faux::methods(cfg = "not(test)")]
+    impl Counter {
+        pub fn new(count: u32) -> Self {
+            Counter { count }
+        }
+
+        pub fn count(&self) -> u32 {
+            self.count
+        }
+    }
+
+    #[test]
+    fn untouched() {
+        // the fields are only accessible in the original struct
+        let counter = Counter { count: 3 };
+        assert_eq!(counter.count, 3);
+        assert_eq!(Counter::new(4).count(), 4);
+        assert_eq!(std::mem::size_of::<Counter>(), std::mem::size_of::<u32>());
+    }
+}
+
+mod functions {
+    #[faux::function(cfg = "test")]
+    pub fn mocked_port() -> u16 {
+        80
+    }
+
+    #[faux::function(cfg = "not(test)")]
+    pub fn original_port() -> u16 {
+        80
+    }
+
+    #[test]
+    fn mocked() {
+        assert_eq!(mocked_port(), 80);
+        faux::when!(mocked_port()).then_return(8080);
+        assert_eq!(mocked_port(), 8080);
+    }
+
+    #[test]
+    fn untouched() {
+        assert_eq!(original_port(), 80);
+    }
+}
@@ -0,0 +1,90 @@
+use std::{
+    sync::{Arc, Condvar, Mutex},
+    thread,
+    time::Duration,
+};
+
+#[faux::create]
+pub struct Cache {}
+
+#[faux::methods]
+impl Cache {
+    pub fn get(&self, _key: u32) -> u32 {
+        unreachable!()
+    }
+}
+
+const THREADS: u32 = 4;
+
+/// Counts the threads inside of a stub
+#[derive(Default)]
+struct Inside {
+    count: Mutex<u32>,
+    changed: Condvar,
+}
+
+impl Inside {
+    /// Enters and waits for `THREADS` threads to be inside at once,
+    /// returning whether they were before the timeout
+    fn enter(&self) -> bool {
+        let mut count = self.count.lock().unwrap();
+        *count += 1;
+        self.changed.notify_all();
+        let (_count, result) = self
+            .changed
+            .wait_timeout_while(count, Duration::from_secs(5), |count| *count < THREADS)
+            .unwrap();
+        !result.timed_out()
+    }
+}
+
+fn call_from_threads(cache: &Arc<Cache>) -> Vec<u32> {
+    let handles: Vec<_> = (0..THREADS)
+        .map(|key| {
+            let cache = cache.clone();
+            thread::spawn(move || cache.get(key))
+        })
+        .collect();
+    handles.into_iter().map(|h| h.join().unwrap()).collect()
+}
+
+#[test]
+fn shared_answers_run_concurrently() {
+    let mut cache = Cache::faux();
+    let inside = Arc::new(Inside::default());
+    {
+        let inside = inside.clone();
+        faux::when!(cache.get).then_shared(move |key| {
+            assert!(inside.enter(), "calls to the stub ran one at a time");
+            key
+        });
+    }
+
+    let cache = Arc::new(cache);
+    assert_eq!(call_from_threads(&cache), vec![0, 1, 2, 3]);
+}
+
+#[test]
+fn returned_values_are_shared() {
+    let mut cache = Cache::faux();
+    // stubs that every invocation checks and skips
+    for key in 100..116 {
+        faux::when!(cache.get(key)).then_return(key);
+    }
+    faux::when!(cache.get(_)).then_return(7);
+
+    let cache = Arc::new(cache);
+    assert_eq!(call_from_threads(&cache), vec![7; THREADS as usize]);
+    faux::await_calls!(cache.get(_), THREADS as usize, Duration::from_secs(1));
+}
+
+#[test]
+fn shared_answers_are_exhausted() {
+    let mut cache = Cache::faux();
+    faux::when!(cache.get).times(2).then_shared(|key| key);
+
+    assert_eq!(cache.get(1), 1);
+    assert_eq!(cache.get(2), 2);
+    let exhausted = std::panic::catch_unwind(std::panic::AssertUnwindSafe(|| cache.get(3)));
+    assert!(exhausted.is_err());
+}
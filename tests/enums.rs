@@ -0,0 +1,107 @@
+#[faux::create]
+#[derive(Clone, Debug)]
+pub enum Backend {
+    Local(String),
+    Remote { url: String, retries: u32 },
+    Memory,
+}
+
+#[faux::methods]
+impl Backend {
+    pub fn local(path: &str) -> Self {
+        Backend::Local(path.to_owned())
+    }
+
+    pub fn remote(url: &str) -> Self {
+        Backend::Remote {
+            url: url.to_owned(),
+            retries: 3,
+        }
+    }
+
+    pub fn memory() -> Self {
+        Backend::Memory
+    }
+
+    pub fn location(&self) -> String {
+        match self {
+            Backend::Local(path) => format!("file://{}", path),
+            Backend::Remote { url, .. } => url.clone(),
+            Backend::Memory => String::from("memory"),
+        }
+    }
+
+    pub fn retry(&mut self) -> bool {
+        match self {
+            Backend::Remote { retries, .. } if *retries > 0 => {
+                *retries -= 1;
+                true
+            }
+            _ => false,
+        }
+    }
+}
+
+#[faux::create]
+pub enum Cached<T> {
+    Hit(T),
+    Miss,
+}
+
+#[faux::methods]
+impl<T: Clone> Cached<T> {
+    pub fn new(value: Option<T>) -> Self {
+        match value {
+            Some(value) => Cached::Hit(value),
+            None => Cached::Miss,
+        }
+    }
+
+    pub fn get(&self) -> Option<T> {
+        match self {
+            Cached::Hit(value) => Some(value.clone()),
+            Cached::Miss => None,
+        }
+    }
+}
+
+#[test]
+fn real_instance() {
+    assert_eq!(Backend::local("/tmp").location(), "file:///tmp");
+    assert_eq!(Backend::memory().location(), "memory");
+
+    let mut remote = Backend::remote("https://example.com");
+    assert_eq!(remote.location(), "https://example.com");
+    assert!(remote.retry());
+    assert!(remote.retry());
+    assert!(remote.retry());
+    assert!(!remote.retry());
+}
+
+#[test]
+fn mocked() {
+    let mut backend = Backend::faux();
+    faux::when!(backend.location()).then_return(String::from("mocked"));
+    faux::when!(backend.retry()).then_return(true);
+
+    assert_eq!(backend.location(), "mocked");
+    assert!(backend.retry());
+}
+
+#[test]
+fn derives() {
+    let local = Backend::local("/tmp");
+    let cloned = local.clone();
+    assert_eq!(cloned.location(), "file:///tmp");
+    assert!(format!("{:?}", local).contains("Local(\"/tmp\")"));
+}
+
+#[test]
+fn generic_enum() {
+    assert_eq!(Cached::new(Some(3)).get(), Some(3));
+    assert_eq!(Cached::<u32>::new(None).get(), None);
+
+    let mut cached = Cached::<u32>::faux();
+    faux::when!(cached.get()).then_return(Some(5));
+    assert_eq!(cached.get(), Some(5));
+}
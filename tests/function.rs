@@ -0,0 +1,94 @@
+use std::thread;
+
+mod fs_helpers {
+    #[faux::function]
+    pub fn read_config(path: &str) -> Result<String, String> {
+        Ok(format!("config at {}", path))
+    }
+
+    #[faux::function]
+    pub fn sum((a, b): (u32, u32)) -> u32 {
+        a + b
+    }
+
+    #[faux::function]
+    pub fn parse<T: std::str::FromStr>(input: &str) -> Option<T> {
+        input.parse().ok()
+    }
+
+    #[faux::function]
+    pub async fn fetch(url: &str) -> String {
+        format!("fetched {}", url)
+    }
+}
+
+#[faux::function]
+fn port() -> u16 {
+    80
+}
+
+fn load(path: &str) -> String {
+    fs_helpers::read_config(path).unwrap_or_else(|e| format!("error: {}", e))
+}
+
+#[test]
+fn real_when_not_stubbed() {
+    assert_eq!(load("a.toml"), "config at a.toml");
+    assert_eq!(fs_helpers::sum((1, 2)), 3);
+    assert_eq!(fs_helpers::parse::<u32>("5"), Some(5));
+    assert_eq!(port(), 80);
+}
+
+#[test]
+fn stubs_function() {
+    faux::when!(fs_helpers::read_config(_)).then(|path| Err(format!("{} not found", path)));
+    faux::when!(fs_helpers::read_config("b.toml")).then(|_| Ok("stubbed".to_string()));
+
+    assert_eq!(load("a.toml"), "error: a.toml not found");
+    assert_eq!(load("b.toml"), "stubbed");
+}
+
+#[test]
+fn stubs_local_function() {
+    faux::when!(port).then_return(8080);
+
+    assert_eq!(port(), 8080);
+}
+
+#[test]
+fn stubs_pattern_arguments() {
+    faux::when!(fs_helpers::sum((1, 2))).then_return(10);
+
+    assert_eq!(fs_helpers::sum((1, 2)), 10);
+}
+
+#[test]
+fn stubs_generic_function_per_type() {
+    faux::when!(fs_helpers::parse::<u32>).then_return(Some(7));
+
+    assert_eq!(fs_helpers::parse::<u32>("5"), Some(7));
+    assert_eq!(fs_helpers::parse::<i64>("5"), Some(5));
+}
+
+#[test]
+fn stubs_async_function() {
+    faux::when!(fs_helpers::fetch).then(|url| format!("stubbed {}", url));
+
+    let fetched = futures::executor::block_on(fs_helpers::fetch("faux"));
+    assert_eq!(fetched, "stubbed faux");
+}
+
+#[test]
+fn stubs_are_thread_local() {
+    faux::when!(port).then_return(8080);
+
+    assert_eq!(thread::spawn(port).join().unwrap(), 80);
+}
+
+#[test]
+#[should_panic(expected = "`function::fs_helpers::read_config` had no suitable stubs")]
+fn stubbed_functions_need_matching_stubs() {
+    faux::when!(fs_helpers::read_config("b.toml")).then(|_| Ok("stubbed".to_string()));
+
+    load("a.toml");
+}
@@ -36,10 +36,13 @@ impl Foo {
     {
         todo!()
     }
+    pub fn run<F: Fn() -> String>(&self, f: F) -> String {
+        f()
+    }
 }
 
 #[faux::create]
-struct AsyncFoo {}
+pub struct AsyncFoo {}
 #[faux::methods]
 impl AsyncFoo {
     pub async fn foo<E: MyTrait>(&self, _e: E) -> E {
@@ -128,9 +131,46 @@ fn generic_two_different_impls() {
     });
 }
 
+fn stub_run<F: Fn() -> String>(foo: &mut Foo, _: &F, output: &'static str) {
+    faux::when!(foo.run::<F>()).then(move |_| output.to_string());
+}
+
+#[test]
+fn generic_closures_from_same_function() {
+    let prefix = String::from("a");
+    let first = move || prefix.clone();
+    let second = || String::from("b");
+
+    let mut foo = Foo::faux();
+    stub_run(&mut foo, &first, "first");
+    stub_run(&mut foo, &second, "second");
+    assert_eq!(foo.run(first), "first");
+    assert_eq!(foo.run(second), "second");
+}
+
+#[test]
+#[should_panic(expected = "`Foo::run<F>` was called but never stubbed")]
+fn generic_closure_stubs_are_not_shared() {
+    let prefix = String::from("a");
+    let first = move || prefix.clone();
+    let second = || String::from("b");
+
+    let mut foo = Foo::faux();
+    stub_run(&mut foo, &first, "first");
+    foo.run(second);
+}
+
 #[test]
 #[should_panic(expected = "`Foo::qux<E>` was called but never stubbed")]
 fn unmocked_faux_panics_with_generic_information() {
     let foo = Foo::faux();
     foo.qux::<Entity>();
 }
+
+#[test]
+#[should_panic(expected = "`AsyncFoo::qux_with_arg<E>` was called but never stubbed")]
+fn generic_impls_stubbed_separately() {
+    let mut qux_with_arg = AsyncFoo::faux();
+    faux::when!(qux_with_arg.qux_with_arg::<Entity>()).then(|_| 100);
+    futures::executor::block_on(qux_with_arg.qux_with_arg::<Entity2>(42));
+}
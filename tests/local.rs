@@ -0,0 +1,89 @@
+use std::{cell::RefCell, rc::Rc, sync::Arc, thread};
+
+#[faux::create(self_type = "Rc")]
+pub struct Cache {}
+
+#[faux::methods(self_type = "Rc")]
+impl Cache {
+    pub fn get(&self, _key: u32) -> Rc<String> {
+        unreachable!()
+    }
+
+    pub fn put(self: Rc<Self>, _key: u32, _value: Rc<String>) {
+        unreachable!()
+    }
+}
+
+#[faux::create]
+pub struct Counter {}
+
+#[faux::methods]
+impl Counter {
+    pub fn next(&self) -> u32 {
+        unreachable!()
+    }
+}
+
+#[test]
+fn returns_non_send_values() {
+    let mut cache = Cache::faux();
+    let value = Rc::new("cached".to_string());
+    faux::when!(cache.get(1)).then_return_local(value.clone());
+    faux::when!(cache.get(2))
+        .once()
+        .then_return_local(Rc::new("once".to_string()));
+
+    assert_eq!(cache.get(1), value);
+    assert_eq!(cache.get(1), value);
+    assert_eq!(*cache.get(2), "once");
+}
+
+#[test]
+fn captures_non_send_values() {
+    let mut cache = Cache::faux();
+    let stored = Rc::new(RefCell::new(vec![]));
+    {
+        let stored = stored.clone();
+        faux::when!(cache.put)
+            .then_local(move |(key, value)| stored.borrow_mut().push((key, value)));
+    }
+
+    let cache = Rc::new(cache);
+    cache.clone().put(1, Rc::new("a".to_string()));
+    cache.put(2, Rc::new("b".to_string()));
+
+    assert_eq!(
+        *stored.borrow(),
+        vec![(1, Rc::new("a".to_string())), (2, Rc::new("b".to_string()))]
+    );
+}
+
+#[test]
+fn panics_on_another_thread() {
+    let mut counter = Counter::faux();
+    let next = Rc::new(RefCell::new(0));
+    faux::when!(counter.next).then_local(move |_| {
+        *next.borrow_mut() += 1;
+        *next.borrow()
+    });
+    assert_eq!(counter.next(), 1);
+
+    let counter = Arc::new(counter);
+    {
+        let counter = counter.clone();
+        let panicked = thread::spawn(move || counter.next()).join();
+        assert!(panicked.is_err());
+    }
+
+    // still usable on its own thread
+    assert_eq!(counter.next(), 2);
+}
+
+#[test]
+fn dropped_on_another_thread() {
+    let mut counter = Counter::faux();
+    let next = Rc::new(0);
+    faux::when!(counter.next).then_local(move |_| *next);
+
+    thread::spawn(move || drop(counter)).join().unwrap();
+}
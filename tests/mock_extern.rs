@@ -0,0 +1,124 @@
+// stands in for a crate whose types cannot be annotated
+mod foreign {
+    pub trait Named {
+        fn name(&self) -> String;
+    }
+
+    pub struct Client {
+        url: String,
+        timeout: u32,
+    }
+
+    impl Client {
+        pub fn new(url: &str) -> Self {
+            Client {
+                url: url.to_string(),
+                timeout: 0,
+            }
+        }
+
+        pub fn connect(url: &str) -> Result<Self, String> {
+            match url {
+                "" => Err("no url".to_string()),
+                url => Ok(Client::new(url)),
+            }
+        }
+
+        pub fn parse(url: &str) -> Option<Self> {
+            url.strip_prefix("http://").map(|_| Client::new(url))
+        }
+
+        pub fn boxed(url: &str) -> Box<Self> {
+            Box::new(Client::new(url))
+        }
+
+        pub fn get(&self, path: &str) -> Result<String, String> {
+            Ok(format!("{}/{} after {}ms", self.url, path, self.timeout))
+        }
+
+        pub fn set_timeout(&mut self, timeout: u32) {
+            self.timeout = timeout;
+        }
+
+        pub async fn fetch(&self, path: &str) -> String {
+            format!("{}/{}", self.url, path)
+        }
+    }
+
+    impl Named for Client {
+        fn name(&self) -> String {
+            "foreign".to_string()
+        }
+    }
+}
+
+faux::mock_extern! {
+    pub struct Client(foreign::Client);
+
+    impl Client {
+        fn new(url: &str) -> Self;
+        fn connect(url: &str) -> Result<Self, String>;
+        fn parse(url: &str) -> Option<Self>;
+        fn boxed(url: &str) -> Box<Self>;
+        fn get(&self, path: &str) -> Result<String, String>;
+        fn set_timeout(&mut self, timeout: u32);
+        async fn fetch(&self, path: &str) -> String;
+    }
+
+    impl foreign::Named for Client {
+        fn name(&self) -> String;
+    }
+}
+
+use foreign::Named;
+
+#[test]
+fn real_instance() {
+    let mut client = Client::new("http://faux");
+    client.set_timeout(10);
+
+    assert_eq!(client.get("a"), Ok("http://faux/a after 10ms".to_string()));
+    assert_eq!(client.name(), "foreign");
+    assert_eq!(
+        futures::executor::block_on(client.fetch("b")),
+        "http://faux/b"
+    );
+}
+
+#[test]
+fn from_foreign() {
+    let client = Client::from(foreign::Client::new("http://faux"));
+
+    assert_eq!(client.get("a"), Ok("http://faux/a after 0ms".to_string()));
+}
+
+#[test]
+fn wrapped_self() {
+    let connected = Client::connect("http://faux").unwrap();
+    assert_eq!(
+        connected.get("a"),
+        Ok("http://faux/a after 0ms".to_string())
+    );
+    assert!(Client::connect("").is_err());
+
+    let parsed = Client::parse("http://faux").unwrap();
+    assert_eq!(parsed.get("a"), Ok("http://faux/a after 0ms".to_string()));
+    assert!(Client::parse("faux").is_none());
+
+    let boxed = Client::boxed("http://faux");
+    assert_eq!(boxed.get("a"), Ok("http://faux/a after 0ms".to_string()));
+}
+
+#[test]
+fn mock_instance() {
+    let mut client = Client::faux();
+    faux::when!(client.get("a")).then_return(Err("not found".to_string()));
+    faux::when!(client.set_timeout).then_return(());
+    faux::when!(client.name).then_return("mock".to_string());
+    faux::when!(client.fetch).then(|path| format!("fetched {}", path));
+
+    client.set_timeout(10);
+    assert_eq!(client.get("a"), Err("not found".to_string()));
+    assert_eq!(client.name(), "mock");
+    assert_eq!(futures::executor::block_on(client.fetch("b")), "fetched b");
+}
@@ -0,0 +1,104 @@
+use std::{thread, time::Duration};
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct Request {
+    path: String,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct Response {
+    status: u16,
+}
+
+fn serve(handler: impl Fn(Request) -> Response, paths: &[&str]) -> Vec<u16> {
+    paths
+        .iter()
+        .map(|path| {
+            handler(Request {
+                path: path.to_string(),
+            })
+            .status
+        })
+        .collect()
+}
+
+#[test]
+fn mocks_closure() {
+    let mut handler = faux::mock_fn::<(Request,), Response>();
+    faux::when!(handler.call(_)).then_return(Response { status: 404 });
+    faux::when!(handler.call(Request {
+        path: "/".to_string()
+    }))
+    .then_return(Response { status: 200 });
+
+    assert_eq!(serve(handler.as_fn(), &["/", "/missing"]), vec![200, 404]);
+}
+
+#[test]
+fn multiple_arguments() {
+    let mut add = faux::mock_fn::<(u32, u32), u32>();
+    faux::when!(add.call(_, 0)).then(|(a, _)| a);
+    faux::when!(add.call(1, 2)).then_return(3);
+
+    let add = add.as_fn();
+    assert_eq!(add(5, 0), 5);
+    assert_eq!(add(1, 2), 3);
+}
+
+#[test]
+fn no_arguments() {
+    let mut next = faux::mock_fn::<(), u32>();
+    faux::when!(next.call).times(2).then_return(1);
+
+    let next = next.into_fn();
+    assert_eq!(next() + next(), 2);
+}
+
+#[test]
+fn moves_across_threads() {
+    let mut handler = faux::mock_fn::<(Request,), Response>();
+    faux::when!(handler.call).then(|request| Response {
+        status: request.path.len() as u16,
+    });
+
+    let handler = handler.into_fn();
+    let statuses = thread::spawn(move || serve(handler, &["/a", "/abc"]))
+        .join()
+        .unwrap();
+    assert_eq!(statuses, vec![2, 4]);
+}
+
+#[test]
+fn waits_for_calls() {
+    let mut handler = faux::mock_fn::<(Request,), Response>();
+    faux::when!(handler.call).then_return(Response { status: 200 });
+
+    serve(handler.as_fn(), &["/a", "/b", "/a"]);
+    faux::await_calls!(handler.call, 3, Duration::from_millis(1));
+}
+
+#[test]
+#[should_panic(expected = "`MockFn::call` had no suitable stubs")]
+fn unmatched_arguments() {
+    let mut handler = faux::mock_fn::<(Request,), Response>();
+    faux::when!(handler.call(Request {
+        path: "/".to_string()
+    }))
+    .then_return(Response { status: 200 });
+
+    serve(handler.as_fn(), &["/missing"]);
+}
+
+#[test]
+#[should_panic(
+    expected = "Previous calls (most recent last):\n  MockFn::call(Request { path: \"/\" })"
+)]
+fn lists_previous_calls() {
+    let mut handler = faux::mock_fn::<(Request,), Response>();
+    faux::when!(handler.call(Request {
+        path: "/".to_string()
+    }))
+    .then_return(Response { status: 200 });
+
+    serve(handler.as_fn(), &["/", "/missing"]);
+}
@@ -0,0 +1,105 @@
+use faux::Mockable;
+
+#[faux::create]
+#[derive(Clone)]
+pub struct Inventory {
+    items: Vec<String>,
+}
+
+#[faux::methods]
+impl Inventory {
+    pub fn new(items: Vec<String>) -> Self {
+        Inventory { items }
+    }
+
+    pub fn count(&self) -> usize {
+        self.items.len()
+    }
+
+    pub fn contains(&self, item: &str) -> bool {
+        self.items.iter().any(|i| i == item)
+    }
+}
+
+#[faux::create]
+pub struct Cache<T> {
+    value: T,
+}
+
+#[faux::methods]
+impl<T: Clone> Cache<T> {
+    pub fn new(value: T) -> Self {
+        Cache { value }
+    }
+
+    pub fn get(&self) -> T {
+        self.value.clone()
+    }
+}
+
+fn fresh<M: Mockable>() -> M {
+    M::faux()
+}
+
+fn assert_no_calls(mock: &impl Mockable) {
+    let store = mock.faux_store().expect("not a mock");
+    assert_eq!(store.calls(), 0, "`{}` was called", store.struct_name());
+}
+
+#[test]
+fn construction() {
+    let inventory: Inventory = fresh();
+    assert!(inventory.is_faux());
+
+    let cache: Cache<u32> = fresh();
+    assert!(cache.is_faux());
+}
+
+#[test]
+fn real_instance() {
+    let inventory = Inventory::new(vec![String::from("apple")]);
+    assert!(!inventory.is_faux());
+    assert!(inventory.faux_store().is_none());
+    assert_eq!(inventory.count(), 1);
+}
+
+#[test]
+fn struct_name() {
+    assert_eq!(Inventory::NAME, "Inventory");
+    assert_eq!(Cache::<u32>::NAME, "Cache");
+
+    let inventory = Inventory::faux();
+    assert_eq!(inventory.faux_store().unwrap().struct_name(), "Inventory");
+}
+
+#[test]
+fn counts_calls() {
+    let mut inventory = Inventory::faux();
+    faux::when!(inventory.count()).then_return(3);
+    faux::when!(inventory.contains("apple")).then_return(true);
+    assert_no_calls(&inventory);
+
+    inventory.count();
+    inventory.contains("apple");
+    inventory.count();
+    assert_eq!(inventory.faux_store().unwrap().calls(), 3);
+
+    // clones share their store
+    let cloned = inventory.clone();
+    cloned.count();
+    assert_eq!(inventory.faux_store().unwrap().calls(), 4);
+}
+
+#[test]
+fn unmet_expectations() {
+    let mut cache = Cache::<u32>::faux();
+    faux::when!(cache.get()).at_least(2).then_return(5);
+
+    let unmet = cache.faux_store().unwrap().unmet_expectations();
+    assert_eq!(unmet.len(), 1);
+    assert!(unmet[0].contains("`get`"), "{:?}", unmet);
+
+    cache.get();
+    cache.get();
+    assert!(cache.faux_store().unwrap().unmet_expectations().is_empty());
+}
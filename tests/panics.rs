@@ -0,0 +1,70 @@
+use std::{
+    panic::{self, AssertUnwindSafe},
+    sync::Arc,
+    thread,
+};
+
+#[faux::create]
+pub struct Foo {}
+
+#[faux::methods]
+impl Foo {
+    pub fn get(&self, _a: u32) -> u32 {
+        unreachable!()
+    }
+}
+
+fn panic_message(f: impl FnOnce()) -> String {
+    let payload = panic::catch_unwind(AssertUnwindSafe(f)).expect_err("expected a panic");
+    match payload.downcast::<String>() {
+        Ok(message) => *message,
+        Err(payload) => payload.downcast::<&str>().unwrap().to_string(),
+    }
+}
+
+#[test]
+fn panicking_stub_on_another_thread() {
+    let mut mock = Foo::faux();
+    faux::when!(mock.get(1)).then_return(1);
+    faux::when!(mock.get(2)).once().then(|_| panic!("boom"));
+
+    let mock = Arc::new(mock);
+    {
+        let mock = mock.clone();
+        let panicked = thread::spawn(move || mock.get(2)).join();
+        assert!(panicked.is_err());
+    }
+
+    // other stubs keep working
+    assert_eq!(mock.get(1), 1);
+
+    let message = panic_message(|| {
+        mock.get(2);
+    });
+    assert!(
+        message.contains("A previous invocation of `Foo::get` panicked: boom"),
+        "{}",
+        message
+    );
+}
+
+#[test]
+fn panicking_matcher() {
+    let mut mock = Foo::faux();
+    faux::when!(mock.get).then_return(0);
+    faux::when!(mock.get(
+        _ = faux::from_fn!(|a: &u32| {
+            assert!(*a < 10, "too big");
+            *a == 1
+        })
+    ))
+    .then_return(1);
+
+    let message = panic_message(|| {
+        mock.get(10);
+    });
+    assert_eq!(message, "too big");
+
+    assert_eq!(mock.get(1), 1);
+    assert_eq!(mock.get(2), 0);
+}
@@ -0,0 +1,68 @@
+mod stubbed {
+    #[faux::create]
+    pub struct Parser {
+        strict: bool,
+    }
+
+    #[faux::methods(private = "stub")]
+    impl Parser {
+        pub fn new(strict: bool) -> Self {
+            Parser { strict }
+        }
+
+        pub fn parse(&self, input: &str) -> Option<u32> {
+            self.validate(input).then_some(input.len() as u32)
+        }
+
+        fn validate(&self, input: &str) -> bool {
+            !self.strict || input.chars().all(char::is_alphanumeric)
+        }
+
+        fn max_len() -> usize {
+            8
+        }
+    }
+
+    #[test]
+    fn real_instance() {
+        let parser = Parser::new(true);
+        assert_eq!(parser.parse("abc"), Some(3));
+        assert_eq!(parser.parse("a c"), None);
+        assert!(parser.validate("abc"));
+        assert_eq!(Parser::max_len(), 8);
+    }
+
+    #[test]
+    fn stubs_private_method() {
+        let mut parser = Parser::faux();
+        faux::when!(parser.validate("a c")).then_return(true);
+
+        assert!(parser.validate("a c"));
+    }
+
+    #[test]
+    fn stubs_private_associated_function() {
+        faux::when!(Parser::max_len).then_return(2);
+
+        assert_eq!(Parser::max_len(), 2);
+    }
+}
+
+mod unstubbed {
+    #[faux::create]
+    pub struct Parser {}
+
+    #[faux::methods]
+    impl Parser {
+        fn validate(&self, _input: &str) -> bool {
+            true
+        }
+    }
+
+    #[test]
+    #[should_panic(expected = "faux error: private methods are not stubbable")]
+    fn private_methods_panic() {
+        let parser = Parser::faux();
+        parser.validate("abc");
+    }
+}
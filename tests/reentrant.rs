@@ -0,0 +1,95 @@
+use std::{
+    panic::{self, AssertUnwindSafe},
+    sync::{Arc, Mutex},
+};
+
+#[faux::create]
+#[derive(Clone)]
+pub struct Foo {}
+
+#[faux::methods]
+impl Foo {
+    pub fn outer(&self) -> u32 {
+        unreachable!()
+    }
+
+    pub fn inner(&self) -> u32 {
+        unreachable!()
+    }
+
+    pub fn countdown(&self, _n: u32) -> u32 {
+        unreachable!()
+    }
+}
+
+/// Lets stubs call the mock they are stubbing, once it is set
+#[derive(Clone, Default)]
+struct Handle(Arc<Mutex<Option<Foo>>>);
+
+impl Handle {
+    fn get(&self) -> Foo {
+        self.0.lock().unwrap().clone().expect("mock was not set")
+    }
+
+    fn set(&self, mock: &Foo) {
+        *self.0.lock().unwrap() = Some(mock.clone());
+    }
+}
+
+#[test]
+fn stub_calls_another_method() {
+    let mut mock = Foo::faux();
+    let handle = Handle::default();
+    {
+        let handle = handle.clone();
+        faux::when!(mock.outer).then(move |_| handle.get().inner() + 1);
+    }
+    faux::when!(mock.inner).then_return(4);
+    handle.set(&mock);
+
+    assert_eq!(mock.outer(), 5);
+}
+
+#[test]
+fn stub_calls_same_method() {
+    let mut mock = Foo::faux();
+    let handle = Handle::default();
+    {
+        let handle = handle.clone();
+        faux::when!(mock.countdown).then(move |n| handle.get().countdown(n - 1) + 1);
+    }
+    faux::when!(mock.countdown(0)).then_return(10);
+    handle.set(&mock);
+
+    assert_eq!(mock.countdown(1), 11);
+}
+
+#[test]
+#[should_panic(
+    expected = "`Foo::countdown` was called by its own stub while that stub was answering on the same thread"
+)]
+fn stub_calls_itself() {
+    let mut mock = Foo::faux();
+    let handle = Handle::default();
+    {
+        let handle = handle.clone();
+        faux::when!(mock.countdown).then(move |n| handle.get().countdown(n - 1) + 1);
+    }
+    faux::when!(mock.countdown(0)).then_return(10);
+    handle.set(&mock);
+
+    mock.countdown(2);
+}
+
+#[test]
+fn panicking_stub_remains_usable() {
+    let mut mock = Foo::faux();
+    faux::when!(mock.countdown).then(|n| {
+        assert!(n > 0, "cannot count down from zero");
+        n - 1
+    });
+
+    let panicked = panic::catch_unwind(AssertUnwindSafe(|| mock.countdown(0)));
+    assert!(panicked.is_err());
+    assert_eq!(mock.countdown(3), 2);
+}
@@ -0,0 +1,66 @@
+#[faux::create]
+pub struct Profile {}
+
+#[derive(Debug, PartialEq)]
+pub struct Settings {
+    theme: String,
+}
+
+#[faux::methods]
+impl Profile {
+    pub fn name(&self) -> &str {
+        unreachable!()
+    }
+
+    pub fn settings(&self) -> &Settings {
+        unreachable!()
+    }
+
+    pub fn tags(&self) -> &'_ [u32] {
+        unreachable!()
+    }
+
+    pub fn pick<'a>(&self, _default: &'a str) -> &'a str {
+        unreachable!()
+    }
+}
+
+#[test]
+fn returns_references() {
+    let mut profile = Profile::faux();
+    faux::when!(profile.name).then_return_ref(String::from("alice"));
+    faux::when!(profile.settings).then_return_ref(Settings {
+        theme: String::from("dark"),
+    });
+    faux::when!(profile.tags).then_return_ref(vec![1, 2, 3]);
+
+    assert_eq!(profile.name(), "alice");
+    assert_eq!(
+        profile.settings(),
+        &Settings {
+            theme: String::from("dark")
+        }
+    );
+    assert_eq!(profile.tags(), &[1, 2, 3]);
+}
+
+#[test]
+fn outlives_exhausted_stub() {
+    let mut profile = Profile::faux();
+    faux::when!(profile.name).then_return_ref(String::from("bob"));
+    faux::when!(profile.name)
+        .times(1)
+        .then_return_ref(String::from("alice"));
+
+    let first = profile.name();
+    let second = profile.name();
+    assert_eq!(first, "alice");
+    assert_eq!(second, "bob");
+}
+
+#[test]
+#[should_panic(expected = "faux: `Profile::pick` may return references that outlive the mock")]
+fn rejects_references_not_borrowed_from_self() {
+    let mut profile = Profile::faux();
+    faux::when!(profile.pick).then_return_ref(String::from("picked"));
+}
@@ -0,0 +1,143 @@
+#[faux::create]
+#[derive(Clone)]
+pub struct Config {}
+
+#[faux::methods]
+impl Config {
+    pub fn get(&self, _key: u32) -> &str {
+        unreachable!()
+    }
+
+    pub fn set(&self, _key: String, _value: u32) {
+        unreachable!()
+    }
+
+    pub fn limit(&self) -> u32 {
+        unreachable!()
+    }
+
+    // named on purpose, to stub it in a scope
+    #[allow(clippy::needless_lifetimes)]
+    pub fn name<'a>(&'a self) -> &'a str {
+        unreachable!()
+    }
+}
+
+#[test]
+fn returns_borrowed_data() {
+    let mut config = Config::faux();
+    let value = String::from("scoped");
+
+    faux::scope(&mut config, |s, config| {
+        faux::when!(config.get(1)).in_scope(s).then(|_| &value);
+        faux::when!(config.get(2)).in_scope(s).then(|_| "other");
+        assert_eq!(config.get(1), "scoped");
+        assert_eq!(config.get(2), "other");
+    });
+}
+
+#[test]
+fn removes_scoped_stubs() {
+    let mut config = Config::faux();
+    faux::when!(config.limit).then_return(1);
+
+    let limit = 10;
+    faux::scope(&mut config, |s, config| {
+        faux::when!(config.limit).in_scope(s).then(|_| limit);
+        assert_eq!(config.limit(), 10);
+    });
+
+    assert_eq!(config.limit(), 1);
+}
+
+#[test]
+fn captures_mutable_borrows() {
+    let mut config = Config::faux();
+    let mut set = vec![];
+
+    faux::scope(&mut config, |s, config| {
+        faux::when!(config.set)
+            .in_scope(s)
+            .then(|(key, value)| set.push((key, value)));
+        config.set("a".to_string(), 1);
+        config.set("b".to_string(), 2);
+    });
+
+    assert_eq!(set, vec![("a".to_string(), 1), ("b".to_string(), 2)]);
+}
+
+#[test]
+fn nested_scopes() {
+    let mut config = Config::faux();
+    let outer = String::from("outer");
+    let inner = String::from("inner");
+
+    faux::scope(&mut config, |s, config| {
+        faux::when!(config.get).in_scope(s).then(|_| &outer);
+        faux::scope(config, |s, config| {
+            faux::when!(config.get).in_scope(s).then(|_| &inner);
+            assert_eq!(config.get(1), "inner");
+        });
+        assert_eq!(config.get(1), "outer");
+    });
+}
+
+#[test]
+#[should_panic(expected = "faux: `Config` may not be cloned while a scope is open on it")]
+fn cannot_clone_in_scope() {
+    let mut config = Config::faux();
+    faux::scope(&mut config, |_, config| {
+        let _ = config.clone();
+    });
+}
+
+#[test]
+#[should_panic(expected = "faux: `Config::get` cannot be stubbed in the scope of another mock")]
+fn scope_of_another_mock() {
+    let mut config = Config::faux();
+    let mut other = Config::faux();
+    faux::scope(&mut config, |s, _| {
+        faux::when!(other.get).in_scope(s).then(|_| "other");
+    });
+}
+
+#[test]
+fn borrows_named_receiver_lifetime() {
+    let mut config = Config::faux();
+    let name = String::from("scoped");
+
+    faux::scope(&mut config, |s, config| {
+        faux::when!(config.name).in_scope(s).then(|_| &name);
+        assert_eq!(config.name(), "scoped");
+    });
+}
+
+#[test]
+fn checks_scoped_stubs_once_closed() {
+    let mut config = Config::faux();
+    let limit = 10;
+
+    let stub_line = line!() + 3;
+    let closed = std::panic::catch_unwind(std::panic::AssertUnwindSafe(|| {
+        faux::scope(&mut config, |s, config| {
+            faux::when!(config.limit)
+                .at_least(2)
+                .in_scope(s)
+                .then(|_| limit);
+            config.limit();
+        });
+    }));
+    let message = closed.unwrap_err().downcast::<String>().unwrap();
+    assert_eq!(
+        *message,
+        format!(
+            "faux: `Config` had stubs that were not called enough times:
+✗ tests/scope.rs:{}:13: `limit` expected at least 2 calls but was called 1 time",
+            stub_line
+        )
+    );
+
+    // the scoped stub was removed nonetheless
+    faux::when!(config.limit).then_return(1);
+    assert_eq!(config.limit(), 1);
+}
@@ -1,3 +1,4 @@
+use faux::sync::CallGate;
 use std::{
     sync::{
         atomic::{AtomicUsize, Ordering},
@@ -118,3 +119,100 @@ fn spin_until(a: &Arc<AtomicUsize>, val: usize) {
         }
     }
 }
+
+#[test]
+fn gate_holds_call_in_flight() {
+    let mut fake = Foo::faux();
+    let gate = CallGate::new();
+    let finished = Arc::new(AtomicUsize::new(0));
+    faux::when!(fake.bar).block_until(&gate).then(|_| {});
+
+    let fake = Arc::new(fake);
+    let bar_thread = {
+        let fake = fake.clone();
+        let finished = finished.clone();
+        thread::spawn(move || {
+            fake.bar();
+            finished.fetch_add(1, Ordering::SeqCst);
+        })
+    };
+
+    assert!(gate.wait_entered_timeout(Duration::from_secs(1)));
+    assert_eq!(gate.entered(), 1);
+    assert_eq!(finished.load(Ordering::SeqCst), 0);
+
+    gate.release();
+    bar_thread.join().unwrap();
+    assert_eq!(finished.load(Ordering::SeqCst), 1);
+
+    // released gates let every call through
+    fake.bar();
+    assert_eq!(gate.entered(), 2);
+}
+
+#[test]
+fn gate_only_holds_calls_that_claimed_its_stub() {
+    let mut fake = Foo::faux();
+    let gate = CallGate::new();
+    faux::when!(fake.bar).then(|_| {});
+    faux::when!(fake.bar)
+        .block_until(&gate)
+        .times(1)
+        .then(|_| {});
+
+    let fake = Arc::new(fake);
+    let bar_thread = {
+        let fake = fake.clone();
+        thread::spawn(move || fake.bar())
+    };
+    assert!(gate.wait_entered_timeout(Duration::from_secs(1)));
+
+    // the gated stub is exhausted by the call it holds
+    let (done, finished) = mpsc::channel();
+    {
+        let fake = fake.clone();
+        thread::spawn(move || {
+            fake.bar();
+            done.send(()).unwrap();
+        });
+    }
+    assert!(
+        finished.recv_timeout(Duration::from_secs(1)).is_ok(),
+        "call held by the gate of another stub"
+    );
+    assert_eq!(gate.entered(), 1);
+
+    gate.release();
+    bar_thread.join().unwrap();
+}
+
+#[test]
+fn gate_holds_call_before_answering() {
+    let mut fake = Foo::faux();
+    let gate = CallGate::new();
+    let answered = Arc::new(AtomicUsize::new(0));
+    {
+        let answered = answered.clone();
+        faux::when!(fake.bar).block_until(&gate).then(move |_| {
+            answered.fetch_add(1, Ordering::SeqCst);
+        });
+    }
+
+    let fake = Arc::new(fake);
+    let bar_threads: Vec<_> = (0..2)
+        .map(|_| {
+            let fake = fake.clone();
+            thread::spawn(move || fake.bar())
+        })
+        .collect();
+
+    // both calls are held at once, before the stub answers them
+    while gate.entered() < 2 {
+        thread::yield_now();
+    }
+    assert_eq!(answered.load(Ordering::SeqCst), 0);
+
+    gate.release();
+    bar_threads.into_iter().for_each(|t| t.join().unwrap());
+    assert_eq!(answered.load(Ordering::SeqCst), 2);
+}
@@ -0,0 +1,61 @@
+use std::{
+    sync::Arc,
+    thread,
+    time::{Duration, Instant},
+};
+
+#[faux::create]
+pub struct Cache {}
+
+#[faux::methods]
+impl Cache {
+    pub fn get(&self, _key: u32) -> u32 {
+        unreachable!()
+    }
+}
+
+const THREADS: u32 = 4;
+const CALLS: u32 = 20_000;
+
+/// Makes `CALLS` calls from each of `threads` threads, each thread to
+/// its own stub
+fn hammer(cache: &Arc<Cache>, threads: u32) -> Duration {
+    let start = Instant::now();
+    let handles: Vec<_> = (0..threads)
+        .map(|key| {
+            let cache = cache.clone();
+            thread::spawn(move || {
+                for _ in 0..CALLS {
+                    assert_eq!(cache.get(key), key);
+                }
+            })
+        })
+        .collect();
+    handles.into_iter().for_each(|h| h.join().unwrap());
+    start.elapsed()
+}
+
+#[test]
+fn throughput_across_threads() {
+    let mut cache = Cache::faux();
+    // stubs that every invocation checks and skips
+    for key in 100..116 {
+        faux::when!(cache.get(key)).then_return(key);
+    }
+    for key in 0..THREADS {
+        faux::when!(cache.get(key)).then_return(key);
+    }
+    let cache = Arc::new(cache);
+
+    for threads in [1, THREADS] {
+        let elapsed = hammer(&cache, threads);
+        let per_sec = f64::from(threads * CALLS) / elapsed.as_secs_f64();
+        println!("{} thread(s): {:.0} calls/s", threads, per_sec);
+    }
+
+    faux::await_calls!(
+        cache.get(_),
+        ((1 + THREADS) * CALLS) as usize,
+        Duration::from_secs(1)
+    );
+}
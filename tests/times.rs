@@ -0,0 +1,182 @@
+use std::panic::{self, AssertUnwindSafe};
+
+#[faux::create]
+pub struct Foo {}
+
+#[faux::methods]
+impl Foo {
+    pub fn get(&self, a: u32) -> u32 {
+        a
+    }
+}
+
+fn panic_message(f: impl FnOnce()) -> String {
+    let payload = panic::catch_unwind(AssertUnwindSafe(f)).expect_err("expected a panic");
+    match payload.downcast::<String>() {
+        Ok(message) => *message,
+        Err(payload) => payload.downcast::<&str>().unwrap().to_string(),
+    }
+}
+
+#[test]
+fn number_only_limits() {
+    let mut mock = Foo::faux();
+    faux::when!(mock.get).times(2).then_return(1);
+    assert_eq!(mock.get(0), 1);
+    assert_eq!(mock.get(0), 1);
+}
+
+#[test]
+#[should_panic(expected = "stub was exhausted")]
+fn range_limits() {
+    let mut mock = Foo::faux();
+    faux::when!(mock.get).times(1..=2).then_return(1);
+    mock.get(0);
+    mock.get(0);
+    mock.get(0);
+}
+
+#[test]
+fn range_is_satisfied() {
+    let mut mock = Foo::faux();
+    faux::when!(mock.get).times(1..3).then_return(1);
+    faux::when!(mock.get(5)).between(0, 1).then_return(5);
+    assert_eq!(mock.get(0), 1);
+    assert_eq!(mock.get(5), 5);
+    assert_eq!(mock.get(0), 1);
+}
+
+#[test]
+fn at_most() {
+    let mut mock = Foo::faux();
+    faux::when!(mock.get).then_return(10);
+    faux::when!(mock.get).at_most(1).then_return(1);
+    assert_eq!(mock.get(0), 1);
+    assert_eq!(mock.get(0), 10);
+}
+
+#[test]
+fn at_least() {
+    let mut mock = Foo::faux();
+    faux::when!(mock.get).at_least(2).then_return(1);
+    for _ in 0..5 {
+        assert_eq!(mock.get(0), 1);
+    }
+}
+
+#[test]
+fn unmet_minimum_panics_on_drop() {
+    let stub_line = line!() + 4;
+    let message = panic_message(|| {
+        let mut mock = Foo::faux();
+        faux::when!(mock.get).then_return(0);
+        faux::when!(mock.get(1)).at_least(2).then_return(1);
+        faux::when!(mock.get(2)).between(1, 3).then_return(2);
+        mock.get(1);
+    });
+
+    assert_eq!(
+        message,
+        format!(
+            "faux: `Foo` had stubs that were not called enough times:
+✗ tests/times.rs:{}:9: `get` expected at least 2 calls but was called 1 time
+✗ tests/times.rs:{}:9: `get` expected between 1 and 3 calls but was never called",
+            stub_line,
+            stub_line + 1,
+        )
+    );
+}
+
+#[test]
+#[should_panic(expected = "had no suitable stubs")]
+fn unmet_minimum_does_not_hide_other_panics() {
+    let mut mock = Foo::faux();
+    faux::when!(mock.get(1)).at_least(1).then_return(1);
+    mock.get(2);
+}
+
+#[test]
+fn never() {
+    let mut mock = Foo::faux();
+    faux::when!(mock.get).then_return(1);
+    faux::when!(mock.get(0)).never();
+    assert_eq!(mock.get(1), 1);
+
+    let stub_line = line!() - 3;
+    let message = panic_message(|| {
+        mock.get(0);
+    });
+    assert!(
+        message.starts_with(
+            "`Foo::get` had no suitable stubs. Closest stub: all arguments matched but it expected to never be called."
+        ),
+        "{}",
+        message
+    );
+    assert!(
+        message.contains(&format!(
+            "✗ tests/times.rs:{}:5: stub was called but expected never",
+            stub_line
+        )),
+        "{}",
+        message
+    );
+}
+
+#[test]
+#[should_panic(expected = "stub was called but expected never")]
+fn zero_times_is_never() {
+    let mut mock = Foo::faux();
+    faux::when!(mock.get).times(0).then_return(1);
+    mock.get(0);
+}
+
+#[test]
+#[allow(clippy::reversed_empty_ranges)]
+fn empty_ranges_panic() {
+    let mut mock = Foo::faux();
+    let empty = [
+        (
+            panic_message(|| drop(faux::when!(mock.get).between(5, 2))),
+            "5..=2",
+        ),
+        (
+            panic_message(|| drop(faux::when!(mock.get).times(5..3))),
+            "5..3",
+        ),
+        (
+            panic_message(|| drop(faux::when!(mock.get).times(..0))),
+            "..0",
+        ),
+    ];
+    for (message, range) in empty {
+        assert_eq!(
+            message,
+            format!(
+                "faux: `{}` is an empty range, no number of calls is within it",
+                range
+            )
+        );
+    }
+}
+
+#[test]
+fn single_calls_are_singular() {
+    let stub_line = line!() + 3;
+    let message = panic_message(|| {
+        let mut mock = Foo::faux();
+        faux::when!(mock.get(1)).at_least(1).then_return(1);
+        faux::when!(mock.get(2)).times(1..=1).then_return(2);
+    });
+
+    assert_eq!(
+        message,
+        format!(
+            "faux: `Foo` had stubs that were not called enough times:
+✗ tests/times.rs:{}:9: `get` expected at least 1 call but was never called
+✗ tests/times.rs:{}:9: `get` expected exactly 1 call but was never called",
+            stub_line,
+            stub_line + 1,
+        )
+    );
+}
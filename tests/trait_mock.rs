@@ -0,0 +1,70 @@
+use std::time::Duration;
+
+#[faux::trait_mock]
+pub trait Storage {
+    fn get(&self, key: &str) -> Option<String>;
+
+    fn set(&mut self, key: String, value: String);
+
+    fn contains(&self, key: &str) -> bool {
+        self.get(key).is_some()
+    }
+}
+
+#[faux::trait_mock(name = "FakeClock")]
+trait Clock: Send {
+    fn now(&self) -> u64;
+}
+
+fn lookup(storage: &dyn Storage, key: &str) -> String {
+    storage.get(key).unwrap_or_default()
+}
+
+fn store(storage: &mut impl Storage, key: &str) {
+    storage.set(key.to_string(), "stored".to_string());
+}
+
+#[test]
+fn mocks_trait() {
+    let mut storage = MockStorage::faux();
+    faux::when!(storage.get(_)).then_return(None);
+    faux::when!(storage.get("a")).then_return(Some("1".to_string()));
+
+    assert_eq!(lookup(&storage, "a"), "1");
+    assert_eq!(lookup(&storage, "b"), "");
+}
+
+#[test]
+fn mocks_mutable_methods() {
+    let mut storage = MockStorage::faux();
+    faux::when!(storage.set(_, "stored".to_string())).then_return(());
+
+    store(&mut storage, "key");
+    faux::await_calls!(storage.set, 1, Duration::from_millis(1));
+}
+
+#[test]
+fn mocks_default_methods() {
+    let mut storage = MockStorage::faux();
+    faux::when!(storage.contains("a")).then_return(true);
+
+    assert!(storage.contains("a"));
+}
+
+#[test]
+#[should_panic(expected = "`MockStorage::contains` was called but never stubbed")]
+fn default_methods_need_stubs() {
+    let mut storage = MockStorage::faux();
+    faux::when!(storage.get).then_return(Some("1".to_string()));
+
+    storage.contains("a");
+}
+
+#[test]
+fn custom_name() {
+    let mut clock = FakeClock::faux();
+    faux::when!(clock.now).then_return(5);
+
+    let clock: Box<dyn Clock> = Box::new(clock);
+    assert_eq!(clock.now(), 5);
+}
@@ -0,0 +1,70 @@
+//! Mocks are stored with their types erased. Requesting one with
+//! types other than the ones it was stubbed with must panic rather
+//! than reinterpret the stub.
+//!
+//! Generated code always requests the right types, so this goes
+//! through the hidden API the macros use.
+
+fn takes_u32(_: (), _: u32) -> u32 {
+    unreachable!()
+}
+
+fn takes_string(_: (), _: String) -> u32 {
+    unreachable!()
+}
+
+fn describe<I>() -> faux::arg_debug::Input<I> {
+    faux::arg_debug::Input {
+        format: |_| String::new(),
+        keep: None,
+    }
+}
+
+#[test]
+#[should_panic(
+    expected = "faux bug: mock was stored with types `(u32, u32)` but requested as \
+                           `(alloc::string::String, u32)`"
+)]
+fn mismatched_types_panic() {
+    let mut faux = faux::Faux::new("Foo");
+    let method = faux::MethodId::new::<()>("get");
+    faux::When::new(takes_u32, method, false, &mut faux).then_return(3);
+
+    let _ = unsafe {
+        faux.call_stub(
+            takes_string,
+            method,
+            String::from("not a u32"),
+            describe(),
+            "",
+        )
+    };
+}
+
+fn takes<F>(_: &F) -> fn((), F) -> u32 {
+    |_, _| unreachable!()
+}
+
+#[test]
+#[should_panic(expected = "faux bug: mock was stored with types")]
+fn types_with_the_same_name_panic() {
+    // both closures are named `type_checks::..::{{closure}}`
+    let first = || 1;
+    let second = || 2;
+
+    let mut faux = faux::Faux::new("Foo");
+    let method = faux::MethodId::new::<()>("get");
+    faux::When::new(takes(&first), method, false, &mut faux).then_return(3);
+
+    let _ = unsafe { faux.call_stub(takes(&second), method, second, describe(), "") };
+}
+
+#[test]
+fn matching_types_are_accepted() {
+    let mut faux = faux::Faux::new("Foo");
+    let method = faux::MethodId::new::<()>("get");
+    faux::When::new(takes_u32, method, false, &mut faux).then_return(3);
+
+    let output = unsafe { faux.call_stub(takes_u32, method, 5, describe(), "") };
+    assert_eq!(output.ok(), Some(3));
+}
@@ -1,3 +1,8 @@
+use std::{
+    borrow::Cow,
+    panic::{self, AssertUnwindSafe},
+};
+
 #[faux::create]
 pub struct Foo {
     a: u32,
@@ -9,6 +14,16 @@ pub struct Data {
     b: u32,
 }
 
+/// Panics if formatted
+#[derive(Clone)]
+pub struct Loud;
+
+impl std::fmt::Debug for Loud {
+    fn fmt(&self, _: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        panic!("arguments formatted even though a stub matched")
+    }
+}
+
 #[faux::methods]
 impl Foo {
     pub fn new(a: u32) -> Self {
@@ -26,6 +41,20 @@ impl Foo {
     pub fn two_args(&self, data: &Data, b: i32) -> u32 {
         data.b + self.a * b as u32
     }
+
+    pub fn two_owned_args(&self, data: Data, b: i32) -> u32 {
+        data.b + self.a * b as u32
+    }
+
+    pub fn loud_args(&self, _: Loud, _: &Loud) {}
+
+    pub fn cow_arg(&self, key: Cow<str>) -> usize {
+        key.len()
+    }
+
+    pub fn anon_cow_arg(&self, key: Cow<'_, str>) -> usize {
+        key.len()
+    }
 }
 
 #[test]
@@ -142,3 +171,124 @@ fn unmatched_args() {
     faux::when!(mock.two_args(_, 4)).then_return(777);
     mock.two_args(&data, 2);
 }
+
+#[test]
+fn mismatch_errors_are_only_built_on_failure() {
+    use faux::matcher::InvocationMatcher;
+
+    struct NeverMatches;
+    impl InvocationMatcher<&Data> for NeverMatches {
+        fn matches(&self, _: &&Data) -> Result<(), String> {
+            panic!("error message built even though a stub matched")
+        }
+
+        fn is_match(&self, _: &&Data) -> bool {
+            false
+        }
+    }
+
+    let mut mock = Foo::faux();
+    faux::when!(mock.one_ref_arg).then_return(5);
+    faux::when!(mock.one_ref_arg)
+        .with_args(NeverMatches)
+        .then_return(10);
+
+    assert_eq!(mock.one_ref_arg(&Data { a: 1, b: 2 }), 5);
+}
+
+#[test]
+#[should_panic(expected = "Diff (- expected, + actual):
+      Data {
+    -     a: 2,
+    +     a: 4,
+          b: 3,
+      }")]
+fn eq_mismatch_shows_diff() {
+    let mut mock = Foo::faux();
+    faux::when!(mock.two_args(Data { a: 2, b: 3 }, 4)).then_return(777);
+    mock.two_args(&Data { a: 4, b: 3 }, 4);
+}
+
+#[test]
+#[should_panic(
+    expected = "`Foo::two_args` had no suitable stubs. Closest stub: argument 1 differed."
+)]
+fn leads_with_closest_stub() {
+    let mut mock = Foo::faux();
+    let data = Data { a: 2, b: 3 };
+    faux::when!(mock.two_args(data.clone(), 4)).then_return(1);
+    faux::when!(mock.two_args(Data { a: 0, b: 0 }, 6)).then_return(2);
+    mock.two_args(&data, 5);
+}
+
+#[test]
+#[should_panic(expected = "Previous calls (most recent last):
+  Foo::two_owned_args(Data { a: 2, b: 3 }, 4)
+  Foo::two_owned_args(Data { a: 2, b: 3 }, 5)")]
+fn lists_previous_calls() {
+    let mut mock = Foo::faux();
+    let data = Data { a: 2, b: 3 };
+    faux::when!(mock.two_owned_args(_, 4)).then_return(1);
+    faux::when!(mock.two_owned_args(_, 5)).once().then_return(2);
+    mock.two_owned_args(data.clone(), 4);
+    mock.two_owned_args(data.clone(), 5);
+    mock.two_owned_args(data, 5);
+}
+
+#[test]
+#[should_panic(
+    expected = "`Foo::two_args` was called 2 times before. Its arguments were not kept to list them."
+)]
+fn counts_previous_calls_that_borrow() {
+    let mut mock = Foo::faux();
+    let data = Data { a: 2, b: 3 };
+    faux::when!(mock.two_args(_, 4)).then_return(1);
+    faux::when!(mock.two_args(_, 5)).once().then_return(2);
+    mock.two_args(&data, 4);
+    mock.two_args(&data, 5);
+    mock.two_args(&data, 5);
+}
+
+#[test]
+fn cow_args() {
+    let mut mock = Foo::faux();
+    faux::when!(mock.cow_arg(Cow::Borrowed("b"))).then_return(1);
+    faux::when!(mock.anon_cow_arg(_)).then(|key| key.len() * 2);
+    let key = String::from("borrowed");
+    assert_eq!(mock.cow_arg(Cow::Borrowed(&key[..1])), 1);
+    assert_eq!(mock.anon_cow_arg(Cow::Borrowed(&key)), 16);
+}
+
+#[test]
+#[should_panic(
+    expected = "`Foo::cow_arg` was called 1 time before. Its arguments were not kept to list them."
+)]
+fn counts_previous_calls_with_elided_lifetimes() {
+    let mut mock = Foo::faux();
+    faux::when!(mock.cow_arg).once().then_return(1);
+    let key = String::from("borrowed");
+    mock.cow_arg(Cow::Borrowed(&key));
+    mock.cow_arg(Cow::Owned(key));
+}
+
+#[test]
+fn arguments_are_only_formatted_on_failure() {
+    let mut mock = Foo::faux();
+    faux::when!(mock.loud_args).then_return(());
+    mock.loud_args(Loud, &Loud);
+}
+
+#[test]
+fn errors_point_to_stub_creation() {
+    let mut mock = Foo::faux();
+    let data = Data { a: 2, b: 3 };
+    let (stub_file, stub_line) = (file!(), line!() + 1);
+    faux::when!(mock.two_args).once().then_return(1);
+    mock.two_args(&data, 4);
+
+    let payload = panic::catch_unwind(AssertUnwindSafe(|| mock.two_args(&data, 4)))
+        .expect_err("expected a panic");
+    let message = payload.downcast::<String>().expect("panic message");
+    let expected = format!("✗ {}:{}:5: stub was exhausted", stub_file, stub_line);
+    assert!(message.contains(&expected), "{}", message);
+}